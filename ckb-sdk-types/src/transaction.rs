@@ -12,7 +12,7 @@ use ckb_types::{
     H256,
 };
 use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Default)]
 pub struct MockCellDep {
@@ -86,6 +86,39 @@ impl MockTransaction {
     pub fn core_transaction(&self) -> TransactionView {
         self.tx.clone().into_view()
     }
+
+    /// Remove cell deps with the same out point and dep_type as an earlier one, return how
+    /// many are removed
+    pub fn dedup_cell_deps(&mut self) -> usize {
+        let cell_deps = self.tx.raw().cell_deps();
+        let mut seen = HashSet::new();
+        let unique_cell_deps = cell_deps
+            .clone()
+            .into_iter()
+            .filter(|cell_dep| seen.insert(cell_dep.clone()))
+            .collect::<Vec<_>>();
+        let removed = cell_deps.len() - unique_cell_deps.len();
+        if removed > 0 {
+            self.tx = self
+                .tx
+                .as_advanced_builder()
+                .set_cell_deps(unique_cell_deps)
+                .build()
+                .data();
+        }
+        removed
+    }
+
+    /// Sort mock cell deps and mock headers into a canonical order. The core
+    /// transaction is consensus-significant so it is never touched.
+    pub fn canonicalize(&mut self) {
+        self.mock_info
+            .cell_deps
+            .sort_by(|a, b| a.cell_dep.as_slice().cmp(b.cell_dep.as_slice()));
+        self.mock_info
+            .header_deps
+            .sort_by(|a, b| a.hash().as_slice().cmp(b.hash().as_slice()));
+    }
 }
 
 pub trait MockResourceLoader {
@@ -97,12 +130,23 @@ pub trait MockResourceLoader {
 pub struct Resource {
     required_cells: HashMap<OutPoint, CellMeta>,
     required_headers: HashMap<Byte32, HeaderView>,
+    skip_missing_headers: bool,
 }
 
 impl Resource {
     pub fn from_both<L: MockResourceLoader>(
+        mock_tx: &MockTransaction,
+        loader: L,
+    ) -> Result<Resource, String> {
+        Resource::from_both_opt(mock_tx, loader, false)
+    }
+
+    /// When `skip_missing_headers` is true, header deps which can not be loaded are left unresolved
+    /// instead of returning an error, scripts reading those headers will fail to load them.
+    pub fn from_both_opt<L: MockResourceLoader>(
         mock_tx: &MockTransaction,
         mut loader: L,
+        skip_missing_headers: bool,
     ) -> Result<Resource, String> {
         let tx = mock_tx.core_transaction();
         let mut required_cells = HashMap::default();
@@ -152,24 +196,29 @@ impl Resource {
         }
 
         for block_hash in tx.header_deps().into_iter() {
-            let header = mock_tx
-                .get_header(&block_hash.unpack(), |block_hash| {
-                    loader.get_header(block_hash)
-                })?
-                .ok_or_else(|| format!("Can not get header: {:x}", block_hash))?;
-            required_headers.insert(block_hash, header);
+            let header_opt = mock_tx.get_header(&block_hash.unpack(), |block_hash| {
+                loader.get_header(block_hash)
+            })?;
+            match header_opt {
+                Some(header) => {
+                    required_headers.insert(block_hash, header);
+                }
+                None if skip_missing_headers => {}
+                None => return Err(format!("Can not get header: {:x}", block_hash)),
+            }
         }
 
         Ok(Resource {
             required_cells,
             required_headers,
+            skip_missing_headers,
         })
     }
 }
 
 impl<'a> HeaderChecker for Resource {
     fn check_valid(&self, block_hash: &Byte32) -> Result<(), ckb_error::Error> {
-        if !self.required_headers.contains_key(block_hash) {
+        if !self.skip_missing_headers && !self.required_headers.contains_key(block_hash) {
             return Err(OutPointError::InvalidHeader(block_hash.clone()).into());
         }
         Ok(())
@@ -224,6 +273,10 @@ pub struct ReprMockInput {
 pub struct ReprMockInfo {
     pub inputs: Vec<ReprMockInput>,
     pub cell_deps: Vec<ReprMockCellDep>,
+    /// Headers returned for the transaction's header_deps before asking the loader (node), can be
+    /// hand authored. The given `hash` is kept as is (not recomputed) so it must match the
+    /// header_deps entry; all other fields (number, epoch, timestamp, dao, ...) are exposed to
+    /// scripts as given through the `load_header` syscall.
     pub header_deps: Vec<json_types::HeaderView>,
 }
 #[derive(Clone, Serialize, Deserialize)]
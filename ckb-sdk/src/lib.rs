@@ -2,6 +2,7 @@ mod basic;
 mod chain;
 mod error;
 mod rpc;
+mod since;
 mod transaction;
 
 pub mod wallet;
@@ -13,9 +14,11 @@ pub use chain::{
 };
 pub use error::Error;
 pub use rpc::HttpRpcClient;
+pub use since::{Since, SinceMetric};
 pub use transaction::{
-    MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction, MockTransactionHelper,
-    ReprMockCellDep, ReprMockInfo, ReprMockInput, ReprMockTransaction,
+    estimate_fee, format_ckb, sum_capacities, MockCellDep, MockInfo, MockInput, MockResourceLoader,
+    MockTransaction, MockTransactionHelper, ReprMockCellDep, ReprMockInfo, ReprMockInput,
+    ReprMockTransaction, ScriptGroupKind, ScriptGroupResult,
 };
 
 pub use ckb_crypto::secp::SECP256K1;
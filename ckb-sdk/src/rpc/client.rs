@@ -45,6 +45,13 @@ pub struct LiveCells(pub Vec<LiveCell>);
 #[derive(Serialize, Deserialize)]
 pub struct CellTransactions(pub Vec<CellTransaction>);
 
+/// Result of `test_tx_pool_accept`, only newer nodes provide this rpc
+#[derive(Serialize, Deserialize)]
+pub struct PoolAcceptResult {
+    pub cycles: Uint64,
+    pub fee: Uint64,
+}
+
 jsonrpc_client!(pub struct RpcClient {
     // Chain
     pub fn get_block(&mut self, hash: H256) -> RpcRequest<OptionBlockView>;
@@ -99,6 +106,7 @@ jsonrpc_client!(pub struct RpcClient {
     // Pool
     pub fn send_transaction(&mut self, tx: Transaction) -> RpcRequest<H256>;
     pub fn tx_pool_info(&mut self) -> RpcRequest<TxPoolInfo>;
+    pub fn test_tx_pool_accept(&mut self, tx: Transaction) -> RpcRequest<PoolAcceptResult>;
 
     // Stats
     pub fn get_blockchain_info(&mut self) -> RpcRequest<ChainInfo>;
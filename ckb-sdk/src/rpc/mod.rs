@@ -6,5 +6,5 @@ pub use ckb_jsonrpc_types::{
 };
 pub use client::{
     CellOutputWithOutPoints, HttpRpcClient, Nodes, OptionBlockView, OptionEpochView, OptionH256,
-    OptionTransactionWithStatus, RpcClient,
+    OptionTransactionWithStatus, PoolAcceptResult, RpcClient,
 };
@@ -0,0 +1,88 @@
+// See: https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0017-tx-valid-since/0017-tx-valid-since.md
+const RELATIVE_FLAG: u64 = 1 << 63;
+const METRIC_FLAG_SHIFT: u64 = 61;
+const METRIC_FLAG_MASK: u64 = 0b11;
+const RESERVED_MASK: u64 = 0x1f00_0000_0000_0000;
+const VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinceMetric {
+    BlockNumber,
+    EpochNumber,
+    Timestamp,
+}
+
+impl SinceMetric {
+    fn from_flag(flag: u64) -> Option<SinceMetric> {
+        match flag {
+            0b00 => Some(SinceMetric::BlockNumber),
+            0b01 => Some(SinceMetric::EpochNumber),
+            0b10 => Some(SinceMetric::Timestamp),
+            _ => None,
+        }
+    }
+
+    fn to_flag(self) -> u64 {
+        match self {
+            SinceMetric::BlockNumber => 0b00,
+            SinceMetric::EpochNumber => 0b01,
+            SinceMetric::Timestamp => 0b10,
+        }
+    }
+}
+
+/// The `since` field of a transaction input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Since(pub u64);
+
+impl Since {
+    pub fn new(metric: SinceMetric, value: u64, relative: bool) -> Result<Since, String> {
+        if value > VALUE_MASK {
+            return Err(format!("since value too large: {}", value));
+        }
+        let mut since = (metric.to_flag() << METRIC_FLAG_SHIFT) | value;
+        if relative {
+            since |= RELATIVE_FLAG;
+        }
+        Ok(Since(since))
+    }
+
+    pub fn is_relative(self) -> bool {
+        self.0 & RELATIVE_FLAG == RELATIVE_FLAG
+    }
+
+    /// Return None when the metric flag or reserved bits are invalid
+    pub fn metric(self) -> Option<SinceMetric> {
+        if self.0 & RESERVED_MASK != 0 {
+            return None;
+        }
+        SinceMetric::from_flag((self.0 >> METRIC_FLAG_SHIFT) & METRIC_FLAG_MASK)
+    }
+
+    pub fn value(self) -> u64 {
+        self.0 & VALUE_MASK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since() {
+        let since = Since::new(SinceMetric::EpochNumber, 42, true).unwrap();
+        assert_eq!(since.0, 0xa000_0000_0000_002a);
+        assert!(since.is_relative());
+        assert_eq!(since.metric(), Some(SinceMetric::EpochNumber));
+        assert_eq!(since.value(), 42);
+
+        let since = Since::new(SinceMetric::BlockNumber, 1024, false).unwrap();
+        assert_eq!(since.0, 1024);
+        assert!(!since.is_relative());
+        assert_eq!(since.metric(), Some(SinceMetric::BlockNumber));
+
+        assert_eq!(Since(0x6000_0000_0000_0000).metric(), None);
+        assert_eq!(Since(0x0100_0000_0000_0000).metric(), None);
+        assert!(Since::new(SinceMetric::Timestamp, VALUE_MASK + 1, false).is_err());
+    }
+}
@@ -2,21 +2,142 @@ use ckb_hash::blake2b_256;
 use ckb_script::TransactionScriptsVerifier;
 use ckb_types::{
     bytes::Bytes,
-    core::{cell::resolve_transaction, Capacity, Cycle, ScriptHashType},
-    packed::{Byte32, CellInput, CellOutput, OutPoint, Script},
+    core::{
+        cell::{resolve_transaction, ResolvedTransaction},
+        Capacity, Cycle, DepType, ScriptHashType,
+    },
+    packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, OutPointVec, Script, Transaction},
     prelude::*,
     H160, H256,
 };
 use fnv::FnvHashSet;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 
-use crate::{GenesisInfo, MIN_SECP_CELL_CAPACITY};
+use crate::{GenesisInfo, MIN_SECP_CELL_CAPACITY, ONE_CKB};
 
 pub use ckb_sdk_types::transaction::{
     MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction, ReprMockCellDep,
     ReprMockInfo, ReprMockInput, ReprMockTransaction, Resource,
 };
 
+// Size of a secp256k1 signature witness in the transaction (data + length header + offset)
+const SECP_WITNESS_SIZE: usize = 65 + 4 + 4;
+// Size reserved for the secp256k1 cell dep filled after the change output is added
+const RESERVED_CELL_DEP_SIZE: usize = 37;
+
+/// Estimate the fee (shannons) of the transaction after its empty witnesses are signed,
+/// `fee_rate` is in shannons/KB
+pub fn estimate_fee(tx: &Transaction, fee_rate: u64) -> u64 {
+    let witnesses = tx.witnesses();
+    let unsigned_size: usize = (0..tx.raw().inputs().len())
+        .map(|idx| match witnesses.get(idx) {
+            Some(witness) if !witness.raw_data().is_empty() => 0,
+            Some(_) => 65,
+            None => SECP_WITNESS_SIZE,
+        })
+        .sum();
+    // The deps are not filled yet if there is none
+    let reserved_size = if tx.raw().cell_deps().is_empty() {
+        RESERVED_CELL_DEP_SIZE
+    } else {
+        0
+    };
+    // The transaction is prefixed with a 4 bytes offset in the block
+    let size = tx.as_slice().len() + 4 + unsigned_size + reserved_size;
+    (size as u64 * fee_rate + 999) / 1000
+}
+
+/// Sum the capacities (shannons) with overflow check, `name` describes them in the error
+pub fn sum_capacities<I>(capacities: I, name: &str) -> Result<u64, String>
+where
+    I: IntoIterator<Item = u64>,
+{
+    capacities
+        .into_iter()
+        .try_fold(Capacity::zero(), |total, capacity| {
+            total.safe_add(Capacity::shannons(capacity))
+        })
+        .map(|total| total.as_u64())
+        .map_err(|_| format!("Capacity overflow: total capacity of {} exceeds u64", name))
+}
+
+/// Format shannons as CKB, e.g. 12.345
+pub fn format_ckb(shannons: u64) -> String {
+    let fraction = format!("{:08}", shannons % ONE_CKB);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (shannons / ONE_CKB).to_string()
+    } else {
+        format!("{}.{}", shannons / ONE_CKB, fraction)
+    }
+}
+
+/// Category of script groups, lock groups are the inputs' lock scripts, type groups are the
+/// type scripts of the inputs and outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptGroupKind {
+    Lock,
+    Type,
+}
+
+impl FromStr for ScriptGroupKind {
+    type Err = String;
+    fn from_str(input: &str) -> Result<ScriptGroupKind, String> {
+        match input {
+            "lock" => Ok(ScriptGroupKind::Lock),
+            "type" => Ok(ScriptGroupKind::Type),
+            _ => Err(format!("Invalid script group kind: {}", input)),
+        }
+    }
+}
+
+impl fmt::Display for ScriptGroupKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            ScriptGroupKind::Lock => write!(f, "lock"),
+            ScriptGroupKind::Type => write!(f, "type"),
+        }
+    }
+}
+
+/// Verification result of a single script group
+#[derive(Debug, Clone)]
+pub struct ScriptGroupResult {
+    pub kind: ScriptGroupKind,
+    pub script_hash: Byte32,
+    pub result: Result<Cycle, String>,
+}
+
+/// Script hashes of the `kind` groups of the resolved transaction, in the order of first appearance
+fn group_script_hashes(rtx: &ResolvedTransaction, kind: ScriptGroupKind) -> Vec<Byte32> {
+    let mut script_hashes = Vec::new();
+    match kind {
+        ScriptGroupKind::Lock => {
+            for cell_meta in &rtx.resolved_inputs {
+                script_hashes.push(cell_meta.cell_output.lock().calc_script_hash());
+            }
+        }
+        ScriptGroupKind::Type => {
+            let outputs = rtx.transaction.outputs().into_iter();
+            for output in rtx
+                .resolved_inputs
+                .iter()
+                .map(|cell_meta| cell_meta.cell_output.clone())
+                .chain(outputs)
+            {
+                if let Some(script) = output.type_().to_opt() {
+                    script_hashes.push(script.calc_script_hash());
+                }
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    script_hashes.retain(|script_hash| seen.insert(script_hash.clone()));
+    script_hashes
+}
+
 pub struct MockTransactionHelper<'a> {
     pub mock_tx: &'a mut MockTransaction,
     live_cell_cache: HashMap<OutPoint, (CellOutput, Bytes)>,
@@ -53,52 +174,115 @@ impl<'a> MockTransactionHelper<'a> {
         Ok(cell)
     }
 
+    fn get_dep_cell<C>(
+        &mut self,
+        out_point: &OutPoint,
+        live_cell_getter: C,
+    ) -> Result<(CellOutput, Bytes), String>
+    where
+        C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+    {
+        let cell = match self.live_cell_cache.get(out_point) {
+            Some(cell) => cell.clone(),
+            None => {
+                let cell = self
+                    .mock_tx
+                    .get_dep_cell(out_point, live_cell_getter)?
+                    .ok_or_else(|| format!("cell dep not found: {}", out_point))?;
+                self.live_cell_cache.insert(out_point.clone(), cell.clone());
+                cell
+            }
+        };
+        Ok(cell)
+    }
+
     /// Add a change cell output use `target_lock` as output lock script, default the same as first input
     pub fn add_change_output<C>(
         &mut self,
         target_lock: Option<Script>,
+        live_cell_getter: C,
+    ) -> Result<u64, String>
+    where
+        C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+    {
+        self.add_change_output_with_fee(target_lock, 0, live_cell_getter)
+    }
+
+    /// Same as `add_change_output`, but the fee of `fee_rate` (shannons/KB) is deducted from
+    /// the change capacity
+    pub fn add_change_output_with_fee<C>(
+        &mut self,
+        target_lock: Option<Script>,
+        fee_rate: u64,
         mut live_cell_getter: C,
     ) -> Result<u64, String>
     where
         C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
     {
-        let mut input_total: u64 = 0;
+        let mut input_capacities = Vec::new();
         let mut first_input_cell = None;
         for input in self.mock_tx.core_transaction().inputs().into_iter() {
             let (output, _) = self.get_input_cell(&input, &mut live_cell_getter)?;
             if first_input_cell.is_none() {
                 first_input_cell = Some(output.clone());
             }
-            input_total += Unpack::<u64>::unpack(&output.capacity());
+            input_capacities.push(Unpack::<u64>::unpack(&output.capacity()));
         }
         if first_input_cell.is_none() {
             return Err(String::from("Must have at least one input"));
         }
+        let input_total = sum_capacities(input_capacities, "inputs")?;
 
-        let output_total: u64 = self
-            .mock_tx
-            .core_transaction()
-            .outputs()
-            .into_iter()
-            .map(|output| Unpack::<u64>::unpack(&output.capacity()))
-            .sum();
-        let delta = input_total.saturating_sub(output_total);
+        let output_total = sum_capacities(
+            self.mock_tx
+                .core_transaction()
+                .outputs()
+                .into_iter()
+                .map(|output| Unpack::<u64>::unpack(&output.capacity())),
+            "outputs",
+        )?;
         if input_total < output_total {
-            Err(format!(
-                "input total({}) < output total({})",
-                input_total, output_total
-            ))
-        } else if delta < *MIN_SECP_CELL_CAPACITY {
+            return Err(format!(
+                "Insufficient capacity: inputs {} CKB < outputs {} CKB, need {} more CKB (plus fee)",
+                format_ckb(input_total),
+                format_ckb(output_total),
+                format_ckb(output_total - input_total)
+            ));
+        }
+        let delta = input_total - output_total;
+        let output_lock = target_lock.unwrap_or_else(|| {
+            first_input_cell
+                .expect("Must have at least one input")
+                .lock()
+        });
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(delta).pack())
+            .lock(output_lock)
+            .build();
+        let tx_with_change = self
+            .mock_tx
+            .tx
+            .as_advanced_builder()
+            .output(output.clone())
+            .output_data(Bytes::default().pack())
+            .build();
+        let fee = estimate_fee(&tx_with_change.data(), fee_rate);
+        if delta < fee {
+            return Err(format!(
+                "Insufficient capacity: inputs {} CKB - outputs {} CKB < fee {} CKB, need {} more CKB",
+                format_ckb(input_total),
+                format_ckb(output_total),
+                format_ckb(fee),
+                format_ckb(fee - delta)
+            ));
+        }
+        let change_capacity = delta - fee;
+        if change_capacity < *MIN_SECP_CELL_CAPACITY {
             Ok(0)
         } else {
-            let output_lock = target_lock.unwrap_or_else(|| {
-                first_input_cell
-                    .expect("Must have at least one input")
-                    .lock()
-            });
-            let output = CellOutput::new_builder()
-                .capacity(Capacity::shannons(delta).pack())
-                .lock(output_lock)
+            let output = output
+                .as_builder()
+                .capacity(Capacity::shannons(change_capacity).pack())
                 .build();
             self.mock_tx.tx = self
                 .mock_tx
@@ -108,16 +292,17 @@ impl<'a> MockTransactionHelper<'a> {
                 .output_data(Bytes::default().pack())
                 .build()
                 .data();
-            Ok(delta)
+            Ok(change_capacity)
         }
     }
 
-    /// Fill deps by code hash or type hash (from mock_deps or system secp256k1 cell)
+    /// Fill deps by code hash or type hash (from mock_deps or system secp256k1/dao cells),
+    /// return the newly added cell deps
     pub fn fill_deps<C>(
         &mut self,
         genesis_info: &GenesisInfo,
         mut live_cell_getter: C,
-    ) -> Result<(), String>
+    ) -> Result<Vec<CellDep>, String>
     where
         C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
     {
@@ -151,17 +336,32 @@ impl<'a> MockTransactionHelper<'a> {
             })
             .collect::<HashMap<_, _>>();
         let secp_type_hash = genesis_info.secp_type_hash();
+        let secp_data_hash = genesis_info.secp_data_hash();
+        let dao_type_hash = genesis_info.dao_type_hash();
+        let dao_data_hash = genesis_info.dao_data_hash();
         let mut insert_dep = |hash_type, code_hash: &Byte32| -> Result<(), String> {
             match (hash_type, code_hash) {
+                (ScriptHashType::Data, data_hash) if data_deps.contains_key(data_hash) => {
+                    cell_deps.insert(data_deps[data_hash].clone());
+                }
+                (ScriptHashType::Data, data_hash) if data_hash == secp_data_hash => {
+                    cell_deps.insert(genesis_info.secp_dep());
+                }
+                (ScriptHashType::Data, data_hash) if data_hash == dao_data_hash => {
+                    cell_deps.insert(genesis_info.dao_dep());
+                }
                 (ScriptHashType::Data, data_hash) => {
-                    let dep = data_deps.get(data_hash).cloned().ok_or_else(|| {
-                        format!("Can not find data hash in mock deps: {}", data_hash)
-                    })?;
-                    cell_deps.insert(dep);
+                    return Err(format!(
+                        "Can not find data hash in mock deps: {}",
+                        data_hash
+                    ));
                 }
                 (ScriptHashType::Type, code_hash) if code_hash == secp_type_hash => {
                     cell_deps.insert(genesis_info.secp_dep());
                 }
+                (ScriptHashType::Type, code_hash) if code_hash == dao_type_hash => {
+                    cell_deps.insert(genesis_info.dao_dep());
+                }
                 (ScriptHashType::Type, type_hash) => {
                     let dep = type_deps.get(type_hash).cloned().ok_or_else(|| {
                         format!("Can not find type hash in mock deps: {}", type_hash)
@@ -180,14 +380,14 @@ impl<'a> MockTransactionHelper<'a> {
                 insert_dep(script.hash_type().unpack(), &script.code_hash())?;
             }
         }
+        let added_cell_deps = cell_deps
+            .difference(&tx.cell_deps().into_iter().collect())
+            .cloned()
+            .collect::<Vec<_>>();
         let new_cell_deps = tx
             .cell_deps()
             .into_iter()
-            .chain(
-                cell_deps
-                    .difference(&tx.cell_deps().into_iter().collect())
-                    .cloned(),
-            )
+            .chain(added_cell_deps.iter().cloned())
             .collect::<Vec<_>>();
         self.mock_tx.tx = self
             .mock_tx
@@ -196,15 +396,31 @@ impl<'a> MockTransactionHelper<'a> {
             .set_cell_deps(new_cell_deps)
             .build()
             .data();
-        Ok(())
+        Ok(added_cell_deps)
     }
 
     /// Compute transaction hash and set witnesses for inputs (search by lock scripts)
     pub fn fill_witnesses<S, C>(
+        &mut self,
+        genesis_info: &GenesisInfo,
+        signer: S,
+        live_cell_getter: C,
+    ) -> Result<(), String>
+    where
+        S: Fn(&H160, &H256) -> Result<[u8; 65], String>,
+        C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+    {
+        self.fill_witnesses_opt(genesis_info, signer, live_cell_getter, None)
+    }
+
+    /// Set witnesses, only the inputs in `sign_indices` are signed if it is given. Witnesses of
+    /// inputs not using the secp256k1 lock are never touched (custom witness layouts).
+    pub fn fill_witnesses_opt<S, C>(
         &mut self,
         genesis_info: &GenesisInfo,
         signer: S,
         mut live_cell_getter: C,
+        sign_indices: Option<&HashSet<usize>>,
     ) -> Result<(), String>
     where
         S: Fn(&H160, &H256) -> Result<[u8; 65], String>,
@@ -219,6 +435,9 @@ impl<'a> MockTransactionHelper<'a> {
             H256::from_slice(&blake2b_256(tx.hash().as_slice())).expect("Convert to H256 failed");
         let mut witness_cache: HashMap<H160, Bytes> = HashMap::default();
         for (idx, input) in tx.inputs().into_iter().enumerate() {
+            if sign_indices.map(|indices| !indices.contains(&idx)) == Some(true) {
+                continue;
+            }
             let lock = self.get_input_cell(&input, &mut live_cell_getter)?.0.lock();
             if &lock.code_hash() == genesis_info.secp_type_hash()
                 && lock.args().raw_data().len() == 20
@@ -262,13 +481,185 @@ impl<'a> MockTransactionHelper<'a> {
         self.fill_witnesses(genesis_info, signer, &mut live_cell_getter)
     }
 
+    /// Check every script used by the transaction can be located among the cell deps by its
+    /// hash_type, report a targeted error when the code is only found by the other hash type.
+    pub fn check_script_hash_types<C>(&mut self, mut live_cell_getter: C) -> Result<(), String>
+    where
+        C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+    {
+        let tx = self.mock_tx.core_transaction();
+        let mut data_hashes = HashSet::new();
+        let mut type_hashes = HashSet::new();
+        for cell_dep in tx.cell_deps().into_iter() {
+            let (output, data) = self.get_dep_cell(&cell_dep.out_point(), &mut live_cell_getter)?;
+            let mut cells = Vec::new();
+            if cell_dep.dep_type().unpack() == DepType::DepGroup {
+                for sub_out_point in OutPointVec::from_slice(&data)
+                    .map_err(|err| format!("Parse dep group data error: {}", err))?
+                    .into_iter()
+                {
+                    cells.push(self.get_dep_cell(&sub_out_point, &mut live_cell_getter)?);
+                }
+            }
+            cells.push((output, data));
+            for (output, data) in cells {
+                data_hashes.insert(CellOutput::calc_data_hash(&data));
+                if let Some(script) = output.type_().to_opt() {
+                    type_hashes.insert(script.calc_script_hash());
+                }
+            }
+        }
+
+        let mut scripts = Vec::new();
+        for (idx, input) in tx.inputs().into_iter().enumerate() {
+            let output = self.get_input_cell(&input, &mut live_cell_getter)?.0;
+            scripts.push((format!("inputs[{}].lock", idx), output.lock()));
+            if let Some(script) = output.type_().to_opt() {
+                scripts.push((format!("inputs[{}].type", idx), script));
+            }
+        }
+        for (idx, output) in tx.outputs().into_iter().enumerate() {
+            if let Some(script) = output.type_().to_opt() {
+                scripts.push((format!("outputs[{}].type", idx), script));
+            }
+        }
+
+        let mut errors = Vec::new();
+        for (name, script) in scripts {
+            let code_hash = script.code_hash();
+            let (found, found_by_other, other_hash_type) = match script.hash_type().unpack() {
+                ScriptHashType::Data => (
+                    data_hashes.contains(&code_hash),
+                    type_hashes.contains(&code_hash),
+                    "type",
+                ),
+                ScriptHashType::Type => (
+                    type_hashes.contains(&code_hash),
+                    data_hashes.contains(&code_hash),
+                    "data",
+                ),
+            };
+            if found {
+                continue;
+            }
+            if found_by_other {
+                errors.push(format!(
+                    "{}: hash_type mismatch, code_hash {:#x} is a {} hash of the cell deps, expected hash_type: {}",
+                    name, code_hash, other_hash_type, other_hash_type
+                ));
+            } else {
+                errors.push(format!(
+                    "{}: can not find script code in cell deps, code_hash: {:#x}",
+                    name, code_hash
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Check every output has at least the capacity its occupied bytes (lock, type and data) need
+    pub fn check_outputs_capacity(&self) -> Result<(), String> {
+        let tx = self.mock_tx.core_transaction();
+        let mut errors = Vec::new();
+        for (idx, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let occupied: u64 = Capacity::bytes(data.len())
+                .and_then(|data_capacity| output.occupied_capacity(data_capacity))
+                .map_err(|err| format!("outputs[{}]: {:?}", idx, err))?
+                .as_u64();
+            let capacity: u64 = output.capacity().unpack();
+            if capacity < occupied {
+                errors.push(format!(
+                    "outputs[{}]: capacity {} CKB < occupied {} CKB, need {} more CKB",
+                    idx,
+                    format_ckb(capacity),
+                    format_ckb(occupied),
+                    format_ckb(occupied - capacity)
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
     /// Verify the transaction by local ScriptVerifier
     pub fn verify<L: MockResourceLoader>(
         &mut self,
         max_cycle: Cycle,
         loader: L,
     ) -> Result<Cycle, String> {
-        let resource = Resource::from_both(self.mock_tx, loader)?;
+        self.verify_opt(max_cycle, loader, false)
+    }
+
+    /// Verify the transaction, header deps not found are skipped if `skip_missing_headers` is true
+    pub fn verify_opt<L: MockResourceLoader>(
+        &mut self,
+        max_cycle: Cycle,
+        loader: L,
+        skip_missing_headers: bool,
+    ) -> Result<Cycle, String> {
+        self.verify_groups(max_cycle, loader, skip_missing_headers, None)
+    }
+
+    /// Same as `verify_opt`, but only the script groups of `only` are verified if given, the
+    /// returned cycles are of those groups
+    pub fn verify_groups<L: MockResourceLoader>(
+        &mut self,
+        max_cycle: Cycle,
+        loader: L,
+        skip_missing_headers: bool,
+        only: Option<ScriptGroupKind>,
+    ) -> Result<Cycle, String> {
+        let resource = Resource::from_both_opt(self.mock_tx, loader, skip_missing_headers)?;
+        let tx = self.mock_tx.core_transaction();
+        let rtx = {
+            let mut seen_inputs = FnvHashSet::default();
+            resolve_transaction(tx, &mut seen_inputs, &resource, &resource)
+                .map_err(|err| format!("Resolve transaction error: {:?}", err))?
+        };
+
+        let mut verifier = TransactionScriptsVerifier::new(&rtx, &resource);
+        verifier.set_debug_printer(|script_hash, message| {
+            println!("script: {:x}, debug: {}", script_hash, message);
+        });
+        let kind = match only {
+            Some(kind) => kind,
+            None => {
+                return verifier
+                    .verify(max_cycle)
+                    .map_err(|err| format!("Verify script error: {:?}", err))
+            }
+        };
+        let script_hashes = group_script_hashes(&rtx, kind);
+        let mut cycles: Cycle = 0;
+        for script_hash in script_hashes {
+            let cycle = verifier
+                .verify_single(&script_hash, max_cycle - cycles)
+                .map_err(|err| format!("Verify script error: {:?}", err))?;
+            cycles = cycles
+                .checked_add(cycle)
+                .filter(|cycles| *cycles <= max_cycle)
+                .ok_or_else(|| format!("Verify script error: exceeded max cycles {}", max_cycle))?;
+        }
+        Ok(cycles)
+    }
+
+    /// Verify every script group on its own with the whole `max_cycle` budget, a failing group
+    /// does not stop the others. Lock groups come first, then type groups.
+    pub fn verify_each_group<L: MockResourceLoader>(
+        &mut self,
+        max_cycle: Cycle,
+        loader: L,
+        skip_missing_headers: bool,
+        only: Option<ScriptGroupKind>,
+    ) -> Result<Vec<ScriptGroupResult>, String> {
+        let resource = Resource::from_both_opt(self.mock_tx, loader, skip_missing_headers)?;
         let tx = self.mock_tx.core_transaction();
         let rtx = {
             let mut seen_inputs = FnvHashSet::default();
@@ -280,9 +671,24 @@ impl<'a> MockTransactionHelper<'a> {
         verifier.set_debug_printer(|script_hash, message| {
             println!("script: {:x}, debug: {}", script_hash, message);
         });
-        verifier
-            .verify(max_cycle)
-            .map_err(|err| format!("Verify script error: {:?}", err))
+        let kinds = match only {
+            Some(kind) => vec![kind],
+            None => vec![ScriptGroupKind::Lock, ScriptGroupKind::Type],
+        };
+        let mut results = Vec::new();
+        for kind in kinds {
+            for script_hash in group_script_hashes(&rtx, kind) {
+                let result = verifier
+                    .verify_single(&script_hash, max_cycle)
+                    .map_err(|err| format!("Verify script error: {:?}", err));
+                results.push(ScriptGroupResult {
+                    kind,
+                    script_hash,
+                    result,
+                });
+            }
+        }
+        Ok(results)
     }
 }
 
@@ -298,6 +704,106 @@ mod test {
     };
     use rand::Rng;
 
+    #[test]
+    fn test_format_ckb() {
+        assert_eq!(format_ckb(0), "0");
+        assert_eq!(format_ckb(61 * ONE_CKB), "61");
+        assert_eq!(format_ckb(12 * ONE_CKB + 34_500_000), "12.345");
+        assert_eq!(format_ckb(1), "0.00000001");
+    }
+
+    #[test]
+    fn test_estimate_fee() {
+        let tx = Transaction::default();
+        assert_eq!(
+            estimate_fee(&tx, 1000),
+            (tx.as_slice().len() + 4 + RESERVED_CELL_DEP_SIZE) as u64
+        );
+        let tx = tx
+            .as_advanced_builder()
+            .cell_dep(CellDep::default())
+            .build()
+            .data();
+        assert_eq!(estimate_fee(&tx, 1000), (tx.as_slice().len() + 4) as u64);
+    }
+
+    #[test]
+    fn test_check_outputs_capacity() {
+        let lock_script = Script::new_builder()
+            .args(Bytes::from(&[0u8; 20][..]).pack())
+            .build();
+        let output = |capacity: Capacity| {
+            CellOutput::new_builder()
+                .capacity(capacity.pack())
+                .lock(lock_script.clone())
+                .build()
+        };
+        let mut mock_tx = MockTransaction::default();
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .output(output(capacity_bytes!(61)))
+            .output_data(Bytes::default().pack())
+            .output(output(capacity_bytes!(61)))
+            .output_data(Bytes::from(&[0u8; 2][..]).pack())
+            .build()
+            .data();
+        let helper = MockTransactionHelper::new(&mut mock_tx);
+        assert_eq!(
+            helper.check_outputs_capacity(),
+            Err("outputs[1]: capacity 61 CKB < occupied 63 CKB, need 2 more CKB".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_capacity_overflow() {
+        assert_eq!(sum_capacities(vec![1, 2, 3], "inputs"), Ok(6));
+        assert!(sum_capacities(vec![u64::max_value() - 1, 2], "inputs")
+            .unwrap_err()
+            .contains("overflow"));
+
+        let lock_script = Script::new_builder()
+            .args(Bytes::from(&[0u8; 20][..]).pack())
+            .build();
+        let mock_input = |index: u32| {
+            let output = CellOutput::new_builder()
+                .capacity(Capacity::shannons(u64::max_value() - 1).pack())
+                .lock(lock_script.clone())
+                .build();
+            MockInput {
+                input: CellInput::new(OutPoint::new(h256!("0xff02").pack(), index), 0),
+                output,
+                data: Bytes::default(),
+            }
+        };
+        let mut mock_tx = MockTransaction::default();
+        mock_tx.mock_info.inputs = vec![mock_input(0), mock_input(1)];
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .inputs(
+                mock_tx
+                    .mock_info
+                    .inputs
+                    .iter()
+                    .map(|mock| mock.input.clone()),
+            )
+            .output(
+                CellOutput::new_builder()
+                    .capacity(capacity_bytes!(100).pack())
+                    .lock(lock_script.clone())
+                    .build(),
+            )
+            .output_data(Bytes::default().pack())
+            .build()
+            .data();
+        let mut helper = MockTransactionHelper::new(&mut mock_tx);
+        assert_eq!(
+            helper.add_change_output_with_fee(None, 1000, |_| Ok(None)),
+            Err("Capacity overflow: total capacity of inputs exceeds u64".to_owned())
+        );
+    }
+
     // NOTE: Should update when block structure changed
     const GENESIS_JSON: &str = include_str!("test-data/genesis_block.json");
 
@@ -435,5 +941,16 @@ mod test {
         helper
             .verify(u64::max_value(), Loader)
             .expect("Verify mock tx failed");
+
+        let groups = helper
+            .verify_each_group(u64::max_value(), Loader, false, None)
+            .expect("Verify script groups failed");
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].kind, ScriptGroupKind::Lock);
+        assert!(groups[0].result.is_ok());
+        let groups = helper
+            .verify_each_group(1, Loader, false, None)
+            .expect("Verify script groups failed");
+        assert!(groups[0].result.is_err(), "Exceeded cycles not reported");
     }
 }
@@ -17,6 +17,7 @@ use crate::subcommands::{
     RpcSubCommand, UtilSubCommand, WalletSubCommand,
 };
 use crate::utils::{
+    arg_parser::{ArgParser, FromStrParser},
     completer::CkbCompleter,
     config::GlobalConfig,
     other::check_alerts,
@@ -24,7 +25,7 @@ use crate::utils::{
 };
 use ckb_sdk::{
     wallet::{KeyStore, ScryptType},
-    GenesisInfo, HttpRpcClient,
+    GenesisInfo, HttpRpcClient, NetworkType,
 };
 
 const ENV_PATTERN: &str = r"\$\{\s*(?P<key>\S+)\s*\}";
@@ -265,16 +266,29 @@ impl InteractiveEnv {
                             self.config.switch_completion_style();
                         }
 
+                        if let Some(fee_rate) = FromStrParser::<u64>::default()
+                            .from_matches_opt(m, "fee-rate", false)?
+                        {
+                            let fee_rate = if fee_rate == 0 { None } else { Some(fee_rate) };
+                            self.config.set_fee_rate(fee_rate);
+                        }
+
+                        if let Some(network) = m.value_of("network") {
+                            self.config.set_network(NetworkType::from_raw_str(network));
+                        }
+
                         self.config.print();
                         let mut file = fs::File::create(self.config_file.as_path())
                             .map_err(|err| format!("open config error: {:?}", err))?;
                         let content = serde_json::to_string_pretty(&json!({
-                            "url": self.config.get_url().to_string(),
+                            "rpc_url": self.config.get_url().to_string(),
                             "color": self.config.color(),
                             "debug": self.config.debug(),
                             "output_format": self.config.output_format().to_string(),
                             "completion_style": self.config.completion_style(),
                             "edit_style": self.config.edit_style(),
+                            "fee_rate": self.config.fee_rate(),
+                            "network": self.config.network().map(NetworkType::to_str),
                         }))
                         .unwrap();
                         file.write_all(content.as_bytes())
@@ -324,6 +338,9 @@ impl InteractiveEnv {
                             &mut self.rpc_client,
                             &mut self.key_store,
                             genesis_info,
+                            self.config.fee_rate(),
+                            self.config.network(),
+                            self.config.get_url().to_string(),
                         )
                         .process(&sub_matches, format, color, debug)?;
                         println!("{}", output);
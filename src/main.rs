@@ -7,10 +7,10 @@ use std::process;
 use std::sync::Arc;
 
 use ckb_build_info::Version;
-use ckb_sdk::HttpRpcClient;
+use ckb_sdk::{HttpRpcClient, NetworkType};
 use ckb_util::RwLock;
 use clap::crate_version;
-use clap::{App, AppSettings, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, Shell, SubCommand};
 #[cfg(unix)]
 use subcommands::TuiSubCommand;
 
@@ -20,7 +20,7 @@ use subcommands::{
     RpcSubCommand, UtilSubCommand, WalletSubCommand,
 };
 use utils::{
-    arg_parser::{ArgParser, UrlParser},
+    arg_parser::{ArgParser, FromStrParser, UrlParser},
     config::GlobalConfig,
     other::{check_alerts, get_key_store},
     printer::{ColorWhen, OutputFormat},
@@ -42,6 +42,19 @@ fn main() -> Result<(), io::Error> {
     let version_short = version.short();
     let version_long = version.long();
     let matches = build_cli(&version_short, &version_long).get_matches();
+    if let ("completions", Some(sub_matches)) = matches.subcommand() {
+        let shell: Shell = sub_matches
+            .value_of("shell")
+            .expect("shell is required")
+            .parse()
+            .expect("shell is validated by possible values");
+        build_cli(&version_short, &version_long).gen_completions_to(
+            "ckb-cli",
+            shell,
+            &mut io::stdout(),
+        );
+        return Ok(());
+    }
 
     let mut env_map: HashMap<String, String> = HashMap::from_iter(env::vars());
     let api_uri_opt = matches
@@ -68,7 +81,11 @@ fn main() -> Result<(), io::Error> {
         file.read_to_string(&mut content)?;
         let configs: serde_json::Value = serde_json::from_str(content.as_str()).unwrap();
         if api_uri_opt.is_none() {
-            if let Some(value) = configs["url"].as_str() {
+            // `url` is the key written by older versions
+            if let Some(value) = configs["rpc_url"]
+                .as_str()
+                .or_else(|| configs["url"].as_str())
+            {
                 config.set_url(value.to_string());
             }
         }
@@ -80,6 +97,12 @@ fn main() -> Result<(), io::Error> {
         config.set_output_format(output_format);
         config.set_completion_style(configs["completion_style"].as_bool().unwrap_or(true));
         config.set_edit_style(configs["edit_style"].as_bool().unwrap_or(true));
+        config.set_fee_rate(configs["fee_rate"].as_u64());
+        config.set_network(
+            configs["network"]
+                .as_str()
+                .and_then(NetworkType::from_raw_str),
+        );
     }
 
     let api_uri = config.get_url().to_string();
@@ -112,12 +135,15 @@ fn main() -> Result<(), io::Error> {
             )
         }),
         ("mock-tx", Some(sub_matches)) => get_key_store(&ckb_cli_dir).and_then(|mut key_store| {
-            MockTxSubCommand::new(&mut rpc_client, &mut key_store, None).process(
-                &sub_matches,
-                output_format,
-                color,
-                debug,
+            MockTxSubCommand::new(
+                &mut rpc_client,
+                &mut key_store,
+                None,
+                config.fee_rate(),
+                config.network(),
+                api_uri.clone(),
             )
+            .process(&sub_matches, output_format, color, debug)
         }),
         ("util", Some(sub_matches)) => UtilSubCommand::new(&mut rpc_client, None).process(
             &sub_matches,
@@ -209,6 +235,16 @@ pub fn build_cli<'a>(version_short: &'a str, version_long: &'a str) -> App<'a, '
         .subcommand(MockTxSubCommand::subcommand("mock-tx"))
         .subcommand(UtilSubCommand::subcommand("util"))
         .subcommand(WalletSubCommand::subcommand())
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("Print the shell completion script")
+                .arg(
+                    Arg::with_name("shell")
+                        .required(true)
+                        .possible_values(&Shell::variants())
+                        .help("The shell to generate the script for"),
+                ),
+        )
         .arg(
             Arg::with_name("url")
                 .long("url")
@@ -288,6 +324,20 @@ pub fn build_interactive() -> App<'static, 'static> {
                     Arg::with_name("edit_style")
                         .long("edit_style")
                         .help("Switch edit style"),
+                )
+                .arg(
+                    Arg::with_name("fee-rate")
+                        .long("fee-rate")
+                        .takes_value(true)
+                        .validator(|input| FromStrParser::<u64>::default().validate(input))
+                        .help("Default fee rate (shannons/KB) for mock-tx, 0 means no default"),
+                )
+                .arg(
+                    Arg::with_name("network")
+                        .long("network")
+                        .takes_value(true)
+                        .possible_values(&["ckb", "ckb_testnet", "none"])
+                        .help("Network of the addresses accepted by mock-tx, none accepts any"),
                 ),
         )
         .subcommand(SubCommand::with_name("info").about("Display global variables"))
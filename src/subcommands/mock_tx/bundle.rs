@@ -0,0 +1,96 @@
+use ckb_sdk::{MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction};
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, HeaderView},
+    packed::{CellDep, CellOutput, OutPoint, OutPointVec},
+    prelude::*,
+    H256,
+};
+
+/// Resolve every cell and header the transaction depends on into mock info, the bundled
+/// mock transaction can be verified without a node.
+pub fn bundle_tx<L: MockResourceLoader>(
+    mock_tx: &MockTransaction,
+    mut loader: L,
+) -> Result<MockTransaction, String> {
+    let tx = mock_tx.core_transaction();
+    let mut mock_info = MockInfo::default();
+
+    for input in tx.inputs().into_iter() {
+        let (output, data) = mock_tx
+            .get_input_cell(&input, |out_point| loader.get_live_cell(out_point))?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        mock_info.inputs.push(MockInput {
+            input,
+            output,
+            data,
+        });
+    }
+
+    let mut push_cell_dep = |cell_dep: CellDep, output: CellOutput, data: Bytes| {
+        if mock_info
+            .cell_deps
+            .iter()
+            .all(|mock| mock.cell_dep.out_point() != cell_dep.out_point())
+        {
+            mock_info.cell_deps.push(MockCellDep {
+                cell_dep,
+                output,
+                data,
+            });
+        }
+    };
+    for cell_dep in tx.cell_deps().into_iter() {
+        let (output, data) = mock_tx
+            .get_dep_cell(&cell_dep.out_point(), |out_point| {
+                loader.get_live_cell(out_point)
+            })?
+            .ok_or_else(|| format!("cell dep not found: {}", cell_dep))?;
+        let dep_type: DepType = cell_dep.dep_type().unpack();
+        if dep_type == DepType::DepGroup {
+            for sub_out_point in OutPointVec::from_slice(&data)
+                .map_err(|err| format!("Parse dep group data error: {}", err))?
+                .into_iter()
+            {
+                let (sub_output, sub_data) = mock_tx
+                    .get_dep_cell(&sub_out_point, |out_point| loader.get_live_cell(out_point))?
+                    .ok_or_else(|| format!("(dep group) cell dep not found: {}", sub_out_point))?;
+                let sub_cell_dep = CellDep::new_builder()
+                    .out_point(sub_out_point)
+                    .dep_type(DepType::Code.pack())
+                    .build();
+                push_cell_dep(sub_cell_dep, sub_output, sub_data);
+            }
+        }
+        push_cell_dep(cell_dep, output, data);
+    }
+
+    for block_hash in tx.header_deps().into_iter() {
+        let block_hash: H256 = block_hash.unpack();
+        let header: HeaderView = mock_tx
+            .get_header(&block_hash, |block_hash| loader.get_header(block_hash))?
+            .ok_or_else(|| format!("header not found: {:#x}", block_hash))?;
+        mock_info.header_deps.push(header);
+    }
+
+    Ok(MockTransaction {
+        mock_info,
+        tx: mock_tx.tx.clone(),
+    })
+}
+
+/// Loader refuses any request, verify with it proves the mock transaction is self-contained
+pub struct OfflineLoader;
+
+impl MockResourceLoader for OfflineLoader {
+    fn get_header(&mut self, hash: H256) -> Result<Option<HeaderView>, String> {
+        Err(format!("header not in bundle: {:#x}", hash))
+    }
+
+    fn get_live_cell(
+        &mut self,
+        out_point: OutPoint,
+    ) -> Result<Option<(CellOutput, Bytes)>, String> {
+        Err(format!("cell not in bundle: {}", out_point))
+    }
+}
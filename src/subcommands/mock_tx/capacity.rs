@@ -0,0 +1,133 @@
+use ckb_sdk::format_ckb;
+use serde_json::json;
+
+use crate::utils::arg_parser::{ArgParser, CapacityParser};
+
+const CKB_SUFFIX: &str = "CKB";
+
+fn for_each_capacity<F>(value: &mut serde_json::Value, f: &mut F) -> Result<(), String>
+where
+    F: FnMut(&mut serde_json::Value) -> Result<(), String>,
+{
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if key == "capacity" {
+                    f(field)?;
+                } else {
+                    for_each_capacity(field, f)?;
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                for_each_capacity(item, f)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The CKB amount of `"600.5 CKB"` or `"600.5 CKB (60050000000 shannons)"`
+fn ckb_amount(input: &str) -> Option<&str> {
+    let amount = match input.find('(') {
+        Some(pos) if input.trim_end().ends_with("shannons)") => &input[..pos],
+        _ => input,
+    }
+    .trim_end();
+    if amount.to_uppercase().ends_with(CKB_SUFFIX) {
+        Some(&amount[..amount.len() - CKB_SUFFIX.len()])
+    } else {
+        None
+    }
+}
+
+/// Accept capacities written as `"600CKB"`/`"600.5 CKB"` or plain integers (shannons), they
+/// are rewritten to the hex form the transaction types expect.
+pub fn parse_capacities(value: &mut serde_json::Value) -> Result<(), String> {
+    for_each_capacity(value, &mut |capacity| {
+        let shannons = match capacity {
+            serde_json::Value::Number(number) => number
+                .as_u64()
+                .ok_or_else(|| format!("Invalid capacity: {}", number))?,
+            serde_json::Value::String(input) if ckb_amount(input).is_some() => {
+                let amount = ckb_amount(input).expect("checked");
+                CapacityParser
+                    .parse(amount)
+                    .map_err(|err| format!("Invalid capacity {:?}: {}", input, err))?
+            }
+            _ => return Ok(()),
+        };
+        *capacity = json!(format!("{:#x}", shannons));
+        Ok(())
+    })
+}
+
+/// Rewrite hex capacities to `"<amount> CKB (<shannons> shannons)"`, the reverse of
+/// `parse_capacities`
+pub fn format_capacities(value: &mut serde_json::Value) -> Result<(), String> {
+    for_each_capacity(value, &mut |capacity| {
+        if let serde_json::Value::String(input) = capacity {
+            if input.starts_with("0x") {
+                let shannons = u64::from_str_radix(&input[2..], 16)
+                    .map_err(|err| format!("Invalid capacity {:?}: {}", input, err))?;
+                *capacity = json!(format!(
+                    "{} CKB ({} shannons)",
+                    format_ckb(shannons),
+                    shannons
+                ));
+            }
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capacities() {
+        let mut value = json!({
+            "outputs": [
+                { "capacity": "600CKB" },
+                { "capacity": "600.5 CKB" },
+                { "capacity": 100 },
+                { "capacity": "0x10" },
+                { "capacity": "1 CKB (100000000 shannons)" },
+            ],
+        });
+        parse_capacities(&mut value).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "outputs": [
+                    { "capacity": "0xdf8475800" },
+                    { "capacity": "0xdfb424880" },
+                    { "capacity": "0x64" },
+                    { "capacity": "0x10" },
+                    { "capacity": "0x5f5e100" },
+                ],
+            })
+        );
+        assert!(parse_capacities(&mut json!({ "capacity": "1.123456789 CKB" })).is_err());
+    }
+
+    #[test]
+    fn test_format_capacities() {
+        let mut value = json!({
+            "outputs": [{ "capacity": "0xdfb424880" }, { "capacity": "0x1" }],
+        });
+        format_capacities(&mut value).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "outputs": [
+                    { "capacity": "600.5 CKB (60050000000 shannons)" },
+                    { "capacity": "0.00000001 CKB (1 shannons)" },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use ckb_jsonrpc_types::Uint64;
+use ckb_sdk::{sum_capacities, HttpRpcClient};
+use ckb_types::{
+    packed::{CellOutput, OutPoint},
+    prelude::*,
+    H256,
+};
+
+/// How live cells are picked until the target capacity is covered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectStrategy {
+    /// Fewest inputs, so the lowest fee (`minimize-inputs`)
+    LargestFirst,
+    /// Spend small cells first, reduces the number of cells of the wallet (`consolidate`)
+    SmallestFirst,
+    /// Least capacity left for the change output
+    MinimizeChange,
+}
+
+impl FromStr for CollectStrategy {
+    type Err = String;
+    fn from_str(input: &str) -> Result<CollectStrategy, String> {
+        match input {
+            "largest-first" | "minimize-inputs" => Ok(CollectStrategy::LargestFirst),
+            "smallest-first" | "consolidate" => Ok(CollectStrategy::SmallestFirst),
+            "minimize-change" => Ok(CollectStrategy::MinimizeChange),
+            _ => Err(format!("Invalid collect strategy: {}", input)),
+        }
+    }
+}
+
+/// Collect live cells of a lock hash by the node's indexer RPC (the lock hash must be indexed)
+pub struct CellCollector<'a> {
+    rpc_client: &'a mut HttpRpcClient,
+    strategy: CollectStrategy,
+}
+
+impl<'a> CellCollector<'a> {
+    pub fn new(rpc_client: &'a mut HttpRpcClient, strategy: CollectStrategy) -> CellCollector<'a> {
+        CellCollector {
+            rpc_client,
+            strategy,
+        }
+    }
+
+    /// Out point and capacity of every live cell of the lock hash
+    pub fn live_cells(&mut self, lock_hash: &H256) -> Result<Vec<(OutPoint, u64)>, String> {
+        const PAGE_SIZE: u64 = 50;
+        let mut page = 0;
+        let mut cells = Vec::new();
+        loop {
+            let live_cells = self
+                .rpc_client
+                .get_live_cells_by_lock_hash(
+                    lock_hash.clone(),
+                    Uint64::from(page),
+                    Uint64::from(PAGE_SIZE),
+                    None,
+                )
+                .call()
+                .map_err(|err| format!("Send get_live_cells_by_lock_hash error: {}", err))?
+                .0;
+            if live_cells.is_empty() {
+                return Ok(cells);
+            }
+            for live_cell in live_cells {
+                let point = live_cell.created_by;
+                let out_point = OutPoint::new(point.tx_hash.pack(), point.index.value() as u32);
+                let output: CellOutput = live_cell.cell_output.into();
+                cells.push((out_point, Unpack::<u64>::unpack(&output.capacity())));
+            }
+            page += 1;
+        }
+    }
+
+    /// The live cells (not in `exclude`) covering `capacity`, picked by the strategy
+    pub fn collect(
+        &mut self,
+        lock_hash: &H256,
+        capacity: u64,
+        exclude: &HashSet<OutPoint>,
+    ) -> Result<Vec<OutPoint>, String> {
+        let cells = self
+            .live_cells(lock_hash)?
+            .into_iter()
+            .filter(|(out_point, _)| !exclude.contains(out_point))
+            .collect::<Vec<_>>();
+        let total = sum_capacities(cells.iter().map(|(_, capacity)| *capacity), "live cells")?;
+        select_cells(cells, capacity, self.strategy).ok_or_else(|| {
+            format!(
+                "Live cells of lock hash {:#x} are not enough: {} < {} (shannons), please make sure the lock hash is indexed by the node (rpc index_lock_hash)",
+                lock_hash, total, capacity
+            )
+        })
+    }
+
+    /// Every live cell of the lock hash, error if there is none
+    pub fn collect_all(&mut self, lock_hash: &H256) -> Result<Vec<OutPoint>, String> {
+        let cells = self.live_cells(lock_hash)?;
+        if cells.is_empty() {
+            return Err(format!(
+                "No live cell found by lock hash {:#x}, please make sure the lock hash is indexed by the node (rpc index_lock_hash)",
+                lock_hash
+            ));
+        }
+        Ok(cells.into_iter().map(|(out_point, _)| out_point).collect())
+    }
+}
+
+/// Pick cells by the strategy until their capacity reaches `capacity`, None if all the cells
+/// together are not enough
+pub fn select_cells(
+    mut cells: Vec<(OutPoint, u64)>,
+    capacity: u64,
+    strategy: CollectStrategy,
+) -> Option<Vec<OutPoint>> {
+    match strategy {
+        CollectStrategy::LargestFirst => cells.sort_by(|a, b| b.1.cmp(&a.1)),
+        CollectStrategy::SmallestFirst => cells.sort_by(|a, b| a.1.cmp(&b.1)),
+        CollectStrategy::MinimizeChange => return select_min_change(cells, capacity),
+    }
+    let (selected, total) = take_until(cells, capacity);
+    if total >= capacity && !selected.is_empty() {
+        Some(
+            selected
+                .into_iter()
+                .map(|(out_point, _)| out_point)
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Take cells in order until their total reaches `capacity` (at least one cell)
+fn take_until(cells: Vec<(OutPoint, u64)>, capacity: u64) -> (Vec<(OutPoint, u64)>, u64) {
+    let mut selected = Vec::new();
+    let mut total: u64 = 0;
+    for (out_point, cell_capacity) in cells {
+        if total >= capacity && !selected.is_empty() {
+            break;
+        }
+        total = total.saturating_add(cell_capacity);
+        selected.push((out_point, cell_capacity));
+    }
+    (selected, total)
+}
+
+/// The smallest single cell covering `capacity` if there is one, otherwise the largest cells
+/// with the last one swapped for the smallest cell still covering the rest
+fn select_min_change(mut cells: Vec<(OutPoint, u64)>, capacity: u64) -> Option<Vec<OutPoint>> {
+    cells.sort_by(|a, b| b.1.cmp(&a.1));
+    if let Some((out_point, _)) = cells
+        .iter()
+        .rev()
+        .find(|(_, cell_capacity)| *cell_capacity >= capacity)
+    {
+        return Some(vec![out_point.clone()]);
+    }
+    let (mut selected, total) = take_until(cells.clone(), capacity);
+    if total < capacity || selected.is_empty() {
+        return None;
+    }
+    let (_, last_capacity) = selected.pop().expect("selected not empty");
+    let rest = capacity.saturating_sub(total - last_capacity);
+    let (last, _) = cells[selected.len()..]
+        .iter()
+        .rev()
+        .find(|(_, cell_capacity)| *cell_capacity >= rest)
+        .cloned()
+        .expect("the last taken cell covers the rest");
+    selected.push((last, 0));
+    Some(
+        selected
+            .into_iter()
+            .map(|(out_point, _)| out_point)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_cells() {
+        let cells = [300, 100, 500, 200]
+            .iter()
+            .enumerate()
+            .map(|(index, capacity)| (OutPoint::new(Default::default(), index as u32), *capacity))
+            .collect::<Vec<_>>();
+        let indices = |selected: Option<Vec<OutPoint>>| {
+            selected.map(|out_points| {
+                out_points
+                    .iter()
+                    .map(|out_point| out_point.index().unpack())
+                    .collect::<Vec<u32>>()
+            })
+        };
+        assert_eq!(
+            indices(select_cells(
+                cells.clone(),
+                600,
+                CollectStrategy::LargestFirst
+            )),
+            Some(vec![2, 0])
+        );
+        assert_eq!(
+            indices(select_cells(
+                cells.clone(),
+                600,
+                CollectStrategy::SmallestFirst
+            )),
+            Some(vec![1, 3, 0])
+        );
+        assert_eq!(
+            indices(select_cells(
+                cells.clone(),
+                0,
+                CollectStrategy::LargestFirst
+            )),
+            Some(vec![2])
+        );
+        assert_eq!(
+            indices(select_cells(
+                cells.clone(),
+                250,
+                CollectStrategy::MinimizeChange
+            )),
+            Some(vec![0])
+        );
+        // 500 + 300 > 700, swap 300 for the 200 cell
+        assert_eq!(
+            indices(select_cells(
+                cells.clone(),
+                700,
+                CollectStrategy::MinimizeChange
+            )),
+            Some(vec![2, 3])
+        );
+        assert_eq!(
+            indices(select_cells(cells, 1101, CollectStrategy::LargestFirst)),
+            None
+        );
+    }
+}
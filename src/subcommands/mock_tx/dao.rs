@@ -0,0 +1,341 @@
+use ckb_sdk::{sum_capacities, MockResourceLoader, MockTransaction};
+use ckb_types::{
+    core::{Capacity, HeaderView},
+    packed::{Byte32, CellInput, CellOutput, OutPoint},
+    prelude::*,
+    H256,
+};
+use serde_json::json;
+
+use super::inspect::dao_deposit_block_number;
+use super::Loader;
+
+/// Loads the cells and headers not in the mock transaction, and tells the block a withdrawing
+/// cell was created in when the header deps do not
+pub(super) trait DaoLoader: MockResourceLoader {
+    fn get_cell_block_hash(&mut self, out_point: &OutPoint) -> Result<Option<H256>, String>;
+}
+
+impl<'a> DaoLoader for Loader<'a> {
+    fn get_cell_block_hash(&mut self, out_point: &OutPoint) -> Result<Option<H256>, String> {
+        if self.offline {
+            return Err(format!("block of cell not in bundle: {}", out_point));
+        }
+        let tx_hash: H256 = out_point.tx_hash().unpack();
+        Ok(self
+            .rpc_client
+            .get_transaction(tx_hash)
+            .call()
+            .map_err(|err| format!("Send get_transaction error: {}", err))?
+            .0
+            .and_then(|tx_with_status| tx_with_status.tx_status.block_hash))
+    }
+}
+
+/// Accumulated rate (AR) of the block's DAO field (c: u64, ar: u64, s: u64, u: u64)
+fn extract_ar(dao: &Byte32) -> u64 {
+    let mut ar_bytes = [0u8; 8];
+    ar_bytes.copy_from_slice(&dao.raw_data()[8..16]);
+    u64::from_le_bytes(ar_bytes)
+}
+
+/// Maximum capacity a DAO cell deposited at `deposit_header` can withdraw when it was
+/// prepared (phase 1) at `withdraw_header`, same as the DAO script: the occupied capacity is
+/// kept, the rest grows with the accumulated rate
+pub fn calculate_maximum_withdraw(
+    output: &CellOutput,
+    data_len: usize,
+    deposit_header: &HeaderView,
+    withdraw_header: &HeaderView,
+) -> Result<u64, String> {
+    let deposit_ar = extract_ar(&deposit_header.dao());
+    let withdraw_ar = extract_ar(&withdraw_header.dao());
+    if deposit_ar == 0 {
+        return Err(format!(
+            "Invalid DAO field of deposit block {:#x}: accumulated rate is 0",
+            deposit_header.hash()
+        ));
+    }
+    let capacity: u64 = output.capacity().unpack();
+    let occupied = Capacity::bytes(data_len)
+        .and_then(|data_capacity| output.occupied_capacity(data_capacity))
+        .map_err(|err| format!("{:?}", err))?
+        .as_u64();
+    let counted = capacity.checked_sub(occupied).ok_or_else(|| {
+        format!(
+            "DAO cell capacity {} < occupied capacity {} (shannons)",
+            capacity, occupied
+        )
+    })?;
+    let withdraw_counted = u128::from(counted) * u128::from(withdraw_ar) / u128::from(deposit_ar);
+    if withdraw_counted > u128::from(u64::max_value()) {
+        return Err("Capacity overflow: maximum withdraw exceeds u64".to_owned());
+    }
+    sum_capacities(vec![withdraw_counted as u64, occupied], "DAO withdraw")
+}
+
+/// Recompute the maximum withdraw of every DAO withdrawing input (phase 2) and check the
+/// outputs don't take more than the inputs can provide. The deposit header is the header dep
+/// at the deposit block number stored in the cell data, the withdraw header is the header dep
+/// of the block the withdrawing cell was created in.
+pub(super) fn check_dao_withdrawals<L: DaoLoader>(
+    loader: &mut L,
+    mock_tx: &MockTransaction,
+    dao_type_hash: &Byte32,
+) -> Result<(serde_json::Value, bool), String> {
+    let tx = mock_tx.core_transaction();
+    let mut header_deps = Vec::new();
+    for hash in tx.header_deps().into_iter() {
+        let hash: H256 = hash.unpack();
+        let header = mock_tx
+            .get_header(&hash, |hash| loader.get_header(hash))?
+            .ok_or_else(|| format!("Header dep not found: {:#x}", hash))?;
+        header_deps.push(header);
+    }
+
+    let mut withdrawals = Vec::new();
+    let mut input_capacities = Vec::new();
+    for (index, input) in tx.inputs().into_iter().enumerate() {
+        let (output, data) = mock_tx
+            .get_input_cell(&input, |out_point| loader.get_live_cell(out_point))?
+            .ok_or_else(|| format!("Input cell not found: {}", input))?;
+        let capacity: u64 = output.capacity().unpack();
+        let is_dao = output
+            .type_()
+            .to_opt()
+            .map(|script| &script.code_hash() == dao_type_hash)
+            .unwrap_or(false);
+        let deposit_number = match dao_deposit_block_number(&data) {
+            Some(number) if is_dao => number,
+            _ => {
+                input_capacities.push(capacity);
+                continue;
+            }
+        };
+        let deposit_header = header_deps
+            .iter()
+            .find(|header| header.number() == deposit_number)
+            .ok_or_else(|| {
+                format!(
+                    "Input #{}: header of the deposit block {} is not in header deps",
+                    index, deposit_number
+                )
+            })?;
+        let withdraw_header = find_withdraw_header(loader, &header_deps, &input, deposit_number)
+            .map_err(|err| format!("Input #{}: {}", index, err))?;
+        let maximum_withdraw =
+            calculate_maximum_withdraw(&output, data.len(), deposit_header, &withdraw_header)
+                .map_err(|err| format!("Input #{}: {}", index, err))?;
+        input_capacities.push(maximum_withdraw);
+        withdrawals.push(json!({
+            "index": index,
+            "deposit-block": deposit_number,
+            "withdraw-block": withdraw_header.number(),
+            "deposited-capacity": capacity,
+            "maximum-withdraw": maximum_withdraw,
+            "compensation": maximum_withdraw.saturating_sub(capacity),
+        }));
+    }
+    let inputs_capacity = sum_capacities(input_capacities, "inputs")?;
+    let outputs_capacity = sum_capacities(
+        tx.outputs()
+            .into_iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity())),
+        "outputs",
+    )?;
+    let ok = outputs_capacity <= inputs_capacity;
+    let report = json!({
+        "withdrawals": withdrawals,
+        "maximum-inputs-capacity": inputs_capacity,
+        "outputs-capacity": outputs_capacity,
+        "ok": ok,
+    });
+    Ok((report, ok))
+}
+
+/// The DAO script loads the header of the withdrawing cell's block, so it is one of the header
+/// deps after the deposit block. The node is only asked which one when there are several.
+fn find_withdraw_header<L: DaoLoader>(
+    loader: &mut L,
+    header_deps: &[HeaderView],
+    input: &CellInput,
+    deposit_number: u64,
+) -> Result<HeaderView, String> {
+    let mut candidates = header_deps
+        .iter()
+        .filter(|header| header.number() > deposit_number)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|header| header.number());
+    candidates.dedup_by_key(|header| header.hash());
+    if candidates.len() == 1 {
+        return Ok(candidates[0].clone());
+    }
+    let out_point = input.previous_output();
+    let block_hash = loader
+        .get_cell_block_hash(&out_point)?
+        .ok_or_else(|| format!("withdrawing cell is not committed: {}", out_point))?;
+    header_deps
+        .iter()
+        .find(|header| header.hash() == block_hash.pack())
+        .cloned()
+        .ok_or_else(|| format!("withdraw block {:#x} is not in header deps", block_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_sdk::MockInput;
+    use ckb_types::{bytes::Bytes, core::HeaderBuilder, packed::Script};
+
+    fn dao_field(ar: u64) -> Byte32 {
+        let mut dao = [0u8; 32];
+        dao[8..16].copy_from_slice(&ar.to_le_bytes());
+        H256(dao).pack()
+    }
+
+    #[test]
+    fn test_calculate_maximum_withdraw() {
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(1_000_000_000_000).pack())
+            .build();
+        let occupied = output
+            .occupied_capacity(Capacity::bytes(8).unwrap())
+            .unwrap()
+            .as_u64();
+        let deposit_header = HeaderBuilder::default()
+            .dao(dao_field(10_000_000_000_000_000))
+            .build();
+        let withdraw_header = HeaderBuilder::default()
+            .dao(dao_field(10_100_000_000_000_000))
+            .build();
+        let maximum =
+            calculate_maximum_withdraw(&output, 8, &deposit_header, &withdraw_header).unwrap();
+        assert_eq!(
+            maximum,
+            (1_000_000_000_000 - occupied) / 100 * 101 + occupied
+        );
+        assert_eq!(
+            calculate_maximum_withdraw(&output, 8, &withdraw_header, &withdraw_header).unwrap(),
+            1_000_000_000_000
+        );
+    }
+
+    /// Tells the block of every cell, never loads a cell or header
+    struct BlockLoader(Option<H256>);
+
+    impl MockResourceLoader for BlockLoader {
+        fn get_header(&mut self, _hash: H256) -> Result<Option<HeaderView>, String> {
+            Ok(None)
+        }
+
+        fn get_live_cell(
+            &mut self,
+            _out_point: OutPoint,
+        ) -> Result<Option<(CellOutput, Bytes)>, String> {
+            Ok(None)
+        }
+    }
+
+    impl DaoLoader for BlockLoader {
+        fn get_cell_block_hash(&mut self, _out_point: &OutPoint) -> Result<Option<H256>, String> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_check_dao_withdrawals() {
+        let dao_type_hash: Byte32 = H256([1u8; 32]).pack();
+        let header = |number: u64, ar| {
+            HeaderBuilder::default()
+                .number(number.pack())
+                .dao(dao_field(ar))
+                .build()
+        };
+        let deposit_header = header(100, 10_000_000_000_000_000);
+        let withdraw_header = header(200, 10_100_000_000_000_000);
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(1_000_000_000_000).pack())
+            .type_(
+                Some(
+                    Script::new_builder()
+                        .code_hash(dao_type_hash.clone())
+                        .build(),
+                )
+                .pack(),
+            )
+            .build();
+        let maximum =
+            calculate_maximum_withdraw(&output, 8, &deposit_header, &withdraw_header).unwrap();
+        let input = CellInput::new(OutPoint::new(H256([2u8; 32]).pack(), 0), 0);
+        let mock_tx = |header_deps: Vec<HeaderView>, output_capacity: u64| {
+            let mut mock_tx = MockTransaction::default();
+            mock_tx.mock_info.inputs.push(MockInput {
+                input: input.clone(),
+                output: output.clone(),
+                data: Bytes::from(&100u64.to_le_bytes()[..]),
+            });
+            mock_tx.tx = mock_tx
+                .tx
+                .as_advanced_builder()
+                .input(input.clone())
+                .output(
+                    CellOutput::new_builder()
+                        .capacity(Capacity::shannons(output_capacity).pack())
+                        .build(),
+                )
+                .header_deps(header_deps.iter().map(|header| header.hash()))
+                .build()
+                .data();
+            mock_tx.mock_info.header_deps = header_deps;
+            mock_tx
+        };
+
+        // The only header dep after the deposit block, the node is not asked
+        let header_deps = vec![deposit_header.clone(), withdraw_header.clone()];
+        let (report, ok) = check_dao_withdrawals(
+            &mut BlockLoader(None),
+            &mock_tx(header_deps.clone(), maximum),
+            &dao_type_hash,
+        )
+        .unwrap();
+        assert!(ok);
+        assert_eq!(report["withdrawals"][0]["withdraw-block"], json!(200));
+        assert_eq!(report["maximum-inputs-capacity"], json!(maximum));
+        let (_, ok) = check_dao_withdrawals(
+            &mut BlockLoader(None),
+            &mock_tx(header_deps, maximum + 1),
+            &dao_type_hash,
+        )
+        .unwrap();
+        assert!(!ok);
+
+        // Several header deps after the deposit block
+        let withdraw_hash: H256 = withdraw_header.hash().unpack();
+        let later_header = header(300, 10_200_000_000_000_000);
+        let header_deps = vec![deposit_header, later_header, withdraw_header];
+        let (report, ok) = check_dao_withdrawals(
+            &mut BlockLoader(Some(withdraw_hash)),
+            &mock_tx(header_deps.clone(), maximum),
+            &dao_type_hash,
+        )
+        .unwrap();
+        assert!(ok);
+        assert_eq!(report["withdrawals"][0]["withdraw-block"], json!(200));
+        let err = check_dao_withdrawals(
+            &mut BlockLoader(None),
+            &mock_tx(header_deps, maximum),
+            &dao_type_hash,
+        )
+        .unwrap_err();
+        assert!(err.contains("not committed"), "{}", err);
+
+        // The block of the withdrawing cell must be a header dep
+        let err = check_dao_withdrawals(
+            &mut BlockLoader(Some(H256([3u8; 32]))),
+            &mock_tx(vec![header(100, 10_000_000_000_000_000)], maximum),
+            &dao_type_hash,
+        )
+        .unwrap_err();
+        assert!(err.contains("is not in header deps"), "{}", err);
+    }
+}
@@ -0,0 +1,63 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+
+use serde_json::json;
+
+/// Emit newline-delimited JSON progress events to stderr (for GUI frontends)
+#[derive(Debug, Clone, Default)]
+pub struct EventEmitter {
+    enabled: bool,
+    /// Elapsed milliseconds of finished phases, in order
+    timings: Rc<RefCell<Vec<(String, u64)>>>,
+}
+
+impl EventEmitter {
+    pub fn new(enabled: bool) -> EventEmitter {
+        EventEmitter {
+            enabled,
+            timings: Default::default(),
+        }
+    }
+
+    /// Run a phase, emit a `start` event before it and a `finish` event after it
+    pub fn phase<T, F>(&self, phase: &str, count: usize, f: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        self.emit(json!({
+            "event": "start",
+            "phase": phase,
+            "count": count,
+        }));
+        let start_time = Instant::now();
+        let result = f();
+        let elapsed_ms = start_time.elapsed().as_millis() as u64;
+        self.timings
+            .borrow_mut()
+            .push((phase.to_owned(), elapsed_ms));
+        self.emit(json!({
+            "event": "finish",
+            "phase": phase,
+            "count": count,
+            "elapsed_ms": elapsed_ms,
+            "success": result.is_ok(),
+        }));
+        result
+    }
+
+    /// Wall-clock time (milliseconds) of each finished phase
+    pub fn timings(&self) -> serde_json::Value {
+        let mut timings = serde_json::Map::new();
+        for (phase, elapsed_ms) in self.timings.borrow().iter() {
+            timings.insert(format!("{}-ms", phase), json!(elapsed_ms));
+        }
+        serde_json::Value::Object(timings)
+    }
+
+    fn emit(&self, event: serde_json::Value) {
+        if self.enabled {
+            eprintln!("{}", event);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use regex::Regex;
+
+/// Known error codes returned by the system secp256k1_blake160_sighash_all lock script
+const SECP_ERROR_CODES: &[(i8, &str)] = &[
+    (-1, "wrong lock script arguments length"),
+    (-2, "encoding error, data is not valid molecule format"),
+    (
+        -3,
+        "syscall error, the script read something not exists (input/witness/cell)",
+    ),
+    (
+        -11,
+        "secp256k1 recover public key failed, the signature may be invalid",
+    ),
+    (-12, "secp256k1 verify signature failed"),
+    (-13, "secp256k1 parse public key failed"),
+    (-14, "secp256k1 parse signature failed"),
+    (-15, "secp256k1 serialize public key failed"),
+    (-21, "script too long"),
+    (
+        -22,
+        "witness size is wrong, the witness of first input in group must be a 65 bytes signature",
+    ),
+    (
+        -31,
+        "public key blake160 hash not match the lock arg, signed by the wrong key",
+    ),
+];
+
+/// Known errors from the script verifier and CKB-VM
+const VM_ERRORS: &[(&str, &str)] = &[
+    (
+        "InvalidCodeHash",
+        "script code not found in cell deps by code_hash/hash_type",
+    ),
+    (
+        "MultipleMatches",
+        "multiple cell deps match the script's type hash",
+    ),
+    (
+        "ExceededMaximumCycles",
+        "script cycles exceed the max cycles limit",
+    ),
+    ("OutOfBound", "script accessed memory out of bound"),
+    (
+        "InvalidInstruction",
+        "script executed an invalid instruction (bad binary or wrong ISA)",
+    ),
+    ("InvalidEcall", "script called an unknown syscall"),
+    ("InvalidElfBits", "script binary is not a 64 bits ELF"),
+    ("ParseError", "script binary is not a valid ELF file"),
+    ("Unaligned", "script accessed unaligned memory"),
+    (
+        "InvalidPermission",
+        "script wrote to read-only memory or executed non-executable memory",
+    ),
+];
+
+/// Explain the script exit code, the meaning of codes depends on the script,
+/// here is the system secp256k1 lock's meaning.
+pub fn explain_code(code: i8) -> String {
+    SECP_ERROR_CODES
+        .iter()
+        .find(|(known_code, _)| *known_code == code)
+        .map(|(_, explain)| {
+            format!(
+                "script exit code {}: {} (system secp256k1 lock)",
+                code, explain
+            )
+        })
+        .unwrap_or_else(|| {
+            format!(
+                "script exit code {}: unknown error code, please check the script's source",
+                code
+            )
+        })
+}
+
+/// Interpret the verification error message, return None if nothing recognized
+pub fn explain_error(error: &str) -> Option<String> {
+    let code_regex = Regex::new(r"ValidationFailure\D*(-?\d+)").expect("valid regex");
+    if let Some(code) = code_regex
+        .captures(error)
+        .and_then(|caps| caps.get(1))
+        .and_then(|code| code.as_str().parse::<i8>().ok())
+    {
+        return Some(explain_code(code));
+    }
+    VM_ERRORS
+        .iter()
+        .find(|(name, _)| error.contains(name))
+        .map(|(name, explain)| format!("{}: {}", name, explain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_error() {
+        assert_eq!(
+            explain_error(
+                "Verify script error: Error { kind: Script, inner: ValidationFailure(-31) }"
+            ),
+            Some(explain_code(-31)),
+        );
+        assert!(explain_error("ValidationFailure: -3")
+            .unwrap()
+            .contains("syscall error"));
+        assert!(explain_error("VMError(InvalidEcall(93))")
+            .unwrap()
+            .starts_with("InvalidEcall"));
+        assert_eq!(explain_error("Resolve transaction error"), None);
+    }
+}
@@ -0,0 +1,163 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use ckb_sdk::{MockTransaction, MockTransactionHelper};
+use ckb_types::{bytes::Bytes, prelude::*};
+use rand::Rng;
+
+use super::bundle::OfflineLoader;
+
+/// Outcome of verifying a mutated transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// The script returned a non-zero exit code, the expected result of a bad input
+    ScriptFailure(String),
+    /// Anything else: VM errors (out of bound, invalid instruction, ...) or a panic
+    Crash(String),
+}
+
+/// Verify the (bundled) mock transaction without a node, a panic is caught as a crash
+pub fn verify(mock_tx: &MockTransaction, max_cycles: u64) -> Outcome {
+    let mut mock_tx = mock_tx.clone();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        MockTransactionHelper::new(&mut mock_tx).verify_opt(max_cycles, OfflineLoader, false)
+    }));
+    match result {
+        Ok(Ok(_)) => Outcome::Passed,
+        Ok(Err(err)) => classify_error(err),
+        Err(_) => Outcome::Crash("verifier panicked".to_owned()),
+    }
+}
+
+pub fn classify_error(err: String) -> Outcome {
+    if err.contains("ValidationFailure") {
+        Outcome::ScriptFailure(err)
+    } else {
+        Outcome::Crash(err)
+    }
+}
+
+/// Randomly mutate one witness, input cell data or output data, return the mutated
+/// transaction and a description of the mutation. None if there is nothing to mutate.
+pub fn mutate<R: Rng>(mock_tx: &MockTransaction, rng: &mut R) -> Option<(MockTransaction, String)> {
+    let witnesses_len = mock_tx.tx.witnesses().len();
+    let inputs_len = mock_tx.mock_info.inputs.len();
+    let outputs_len = mock_tx.tx.raw().outputs_data().len();
+    let total = witnesses_len + inputs_len + outputs_len;
+    if total == 0 {
+        return None;
+    }
+    let mut mock_tx = mock_tx.clone();
+    let target = rng.gen_range(0, total);
+    let desc = if target < witnesses_len {
+        let mut witnesses: Vec<_> = mock_tx.tx.witnesses().into_iter().collect();
+        let (data, op) = mutate_bytes(&witnesses[target].raw_data(), rng);
+        witnesses[target] = data.pack();
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build()
+            .data();
+        format!("witnesses[{}]: {}", target, op)
+    } else if target < witnesses_len + inputs_len {
+        let index = target - witnesses_len;
+        let mock_input = &mut mock_tx.mock_info.inputs[index];
+        let (data, op) = mutate_bytes(&mock_input.data, rng);
+        mock_input.data = data;
+        format!("inputs[{}].data: {}", index, op)
+    } else {
+        let index = target - witnesses_len - inputs_len;
+        let mut outputs_data: Vec<_> = mock_tx.tx.raw().outputs_data().into_iter().collect();
+        let (data, op) = mutate_bytes(&outputs_data[index].raw_data(), rng);
+        outputs_data[index] = data.pack();
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .set_outputs_data(outputs_data)
+            .build()
+            .data();
+        format!("outputs_data[{}]: {}", index, op)
+    };
+    Some((mock_tx, desc))
+}
+
+/// Flip a bit, change a byte, truncate or extend the bytes, the result always differs
+fn mutate_bytes<R: Rng>(data: &[u8], rng: &mut R) -> (Bytes, String) {
+    let mut data = data.to_vec();
+    let op = if data.is_empty() {
+        rng.gen_range(3, 4)
+    } else {
+        rng.gen_range(0, 4)
+    };
+    let desc = match op {
+        0 => {
+            let index = rng.gen_range(0, data.len());
+            let bit = rng.gen_range(0u8, 8);
+            data[index] ^= 1 << bit;
+            format!("flip bit {} of byte {}", bit, index)
+        }
+        1 => {
+            let index = rng.gen_range(0, data.len());
+            let mask = rng.gen_range(1u16, 256) as u8;
+            data[index] ^= mask;
+            format!("xor byte {} with {:#04x}", index, mask)
+        }
+        2 => {
+            let len = rng.gen_range(0, data.len());
+            data.truncate(len);
+            format!("truncate to {} bytes", len)
+        }
+        _ => {
+            let extra = rng.gen_range(1, 33);
+            data.extend((0..extra).map(|_| rng.gen::<u8>()));
+            format!("append {} random bytes", extra)
+        }
+    };
+    (Bytes::from(data), desc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_sdk::MockInfo;
+    use ckb_types::core::TransactionBuilder;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_mutate() {
+        let mock_tx = MockTransaction {
+            mock_info: MockInfo::default(),
+            tx: TransactionBuilder::default()
+                .witness(Bytes::from(vec![0u8; 65]).pack())
+                .build()
+                .data(),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let (mutated, desc) = mutate(&mock_tx, &mut rng).unwrap();
+            assert!(desc.starts_with("witnesses[0]: "));
+            assert_ne!(mutated.tx.as_slice(), mock_tx.tx.as_slice());
+        }
+
+        let empty = MockTransaction {
+            mock_info: MockInfo::default(),
+            tx: TransactionBuilder::default().build().data(),
+        };
+        assert!(mutate(&empty, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_classify_error() {
+        assert!(match classify_error(
+            "Error { kind: Script, inner: ValidationFailure(-31) }".to_owned()
+        ) {
+            Outcome::ScriptFailure(_) => true,
+            _ => false,
+        });
+        assert!(match classify_error("VMError(OutOfBound)".to_owned()) {
+            Outcome::Crash(_) => true,
+            _ => false,
+        });
+    }
+}
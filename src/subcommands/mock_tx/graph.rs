@@ -0,0 +1,109 @@
+use ckb_sdk::MockTransaction;
+use ckb_types::{
+    core::{DepType, ScriptHashType},
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec, Script},
+    prelude::*,
+};
+
+fn out_point_label(out_point: &OutPoint) -> String {
+    let index: u32 = out_point.index().unpack();
+    format!("{:#x}:{}", out_point.tx_hash(), index)
+}
+
+fn script_matches(script: &Script, data_hash: &Byte32, type_hash: Option<&Byte32>) -> bool {
+    let hash_type: ScriptHashType = script.hash_type().unpack();
+    match hash_type {
+        ScriptHashType::Data => &script.code_hash() == data_hash,
+        ScriptHashType::Type => type_hash == Some(&script.code_hash()),
+    }
+}
+
+/// Export the mock transaction as a Graphviz DOT graph, cell deps are only linked to the
+/// scripts using them when the dep cells are provided in mock info.
+pub fn to_dot(mock_tx: &MockTransaction) -> String {
+    let tx = mock_tx.core_transaction();
+    let mut lines = vec![
+        "digraph mock_tx {".to_owned(),
+        "    rankdir=LR;".to_owned(),
+        format!("    tx [shape=box, label=\"tx\\n{:#x}\"];", tx.hash()),
+    ];
+
+    let mut scripts: Vec<(String, Script)> = Vec::new();
+    for (idx, input) in tx.inputs().into_iter().enumerate() {
+        let node = format!("input_{}", idx);
+        lines.push(format!(
+            "    {} [shape=ellipse, label=\"input#{}\\n{}\"];",
+            node,
+            idx,
+            out_point_label(&input.previous_output())
+        ));
+        lines.push(format!("    {} -> tx;", node));
+        if let Ok(Some((output, _))) = mock_tx.get_input_cell(&input, |_| Ok(None)) {
+            scripts.push((node.clone(), output.lock()));
+            if let Some(type_script) = output.type_().to_opt() {
+                scripts.push((node, type_script));
+            }
+        }
+    }
+    for (idx, output) in tx.outputs().into_iter().enumerate() {
+        let node = format!("output_{}", idx);
+        let capacity: u64 = output.capacity().unpack();
+        lines.push(format!(
+            "    {} [shape=ellipse, label=\"output#{}\\ncapacity: {}\"];",
+            node, idx, capacity
+        ));
+        lines.push(format!("    tx -> {};", node));
+        if let Some(type_script) = output.type_().to_opt() {
+            scripts.push((node, type_script));
+        }
+    }
+    for (idx, cell_dep) in tx.cell_deps().into_iter().enumerate() {
+        let node = format!("cell_dep_{}", idx);
+        let out_point = cell_dep.out_point();
+        lines.push(format!(
+            "    {} [shape=component, label=\"cell_dep#{}\\n{}\"];",
+            node,
+            idx,
+            out_point_label(&out_point)
+        ));
+        lines.push(format!("    {} -> tx [style=dashed];", node));
+        let mut dep_cells = Vec::new();
+        if let Ok(Some((output, data))) = mock_tx.get_dep_cell(&out_point, |_| Ok(None)) {
+            let dep_type: DepType = cell_dep.dep_type().unpack();
+            if dep_type == DepType::DepGroup {
+                if let Ok(sub_out_points) = OutPointVec::from_slice(&data) {
+                    for sub_out_point in sub_out_points.into_iter() {
+                        if let Ok(Some(cell)) = mock_tx.get_dep_cell(&sub_out_point, |_| Ok(None)) {
+                            dep_cells.push(cell);
+                        }
+                    }
+                }
+            }
+            dep_cells.push((output, data));
+        }
+        for (output, data) in dep_cells {
+            let data_hash = CellOutput::calc_data_hash(&data);
+            let type_hash = output
+                .type_()
+                .to_opt()
+                .map(|script| script.calc_script_hash());
+            for (script_node, script) in &scripts {
+                if script_matches(script, &data_hash, type_hash.as_ref()) {
+                    lines.push(format!(
+                        "    {} -> {} [style=dotted, label=\"code\"];",
+                        node, script_node
+                    ));
+                }
+            }
+        }
+    }
+    for (idx, block_hash) in tx.header_deps().into_iter().enumerate() {
+        lines.push(format!(
+            "    header_dep_{} [shape=note, label=\"header_dep#{}\\n{:#x}\"];",
+            idx, idx, block_hash
+        ));
+        lines.push(format!("    header_dep_{} -> tx [style=dashed];", idx));
+    }
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+
+use ckb_sdk::MockTransaction;
+use ckb_types::{
+    bytes::Bytes,
+    core::{DepType, ScriptHashType},
+    packed::{Byte32, CellOutput, OutPoint, OutPointVec, Script},
+    prelude::*,
+    H160, H256,
+};
+use faster_hex::hex_string;
+use serde_json::json;
+
+fn out_point_json(out_point: &OutPoint) -> serde_json::Value {
+    let index: u32 = out_point.index().unpack();
+    json!({
+        "tx_hash": format!("{:#x}", out_point.tx_hash()),
+        "index": index,
+    })
+}
+
+/// Hex of the data, only the first `limit` bytes are shown if it is longer
+fn data_hex(data: &[u8], limit: usize) -> String {
+    if data.len() > limit {
+        format!("0x{}...", hex_string(&data[..limit]).expect("hex"))
+    } else {
+        format!("0x{}", hex_string(data).expect("hex"))
+    }
+}
+
+/// sUDT cells (typed cells) store the amount as the leading 16 bytes (u128 little endian)
+fn sudt_amount(output: &CellOutput, data: &[u8]) -> Option<u128> {
+    if output.type_().to_opt().is_none() || data.len() < 16 {
+        return None;
+    }
+    let mut amount_bytes = [0u8; 16];
+    amount_bytes.copy_from_slice(&data[..16]);
+    Some(u128::from_le_bytes(amount_bytes))
+}
+
+/// Nervos DAO cells withdrawn from a deposit store the deposit block number as 8 bytes (u64
+/// little endian), the data of a deposit cell is zeros or empty
+pub fn dao_deposit_block_number(data: &[u8]) -> Option<u64> {
+    if data.len() != 8 {
+        return None;
+    }
+    let mut number_bytes = [0u8; 8];
+    number_bytes.copy_from_slice(data);
+    Some(u64::from_le_bytes(number_bytes)).filter(|number| *number > 0)
+}
+
+/// Kind of the type script of the cell, null for an untyped cell. The DAO is recognized by
+/// the genesis DAO type hash, the chain has no system sUDT cell to recognize sUDT by.
+pub fn type_kind(output: &CellOutput, dao_type_hash: &Byte32) -> Option<&'static str> {
+    output.type_().to_opt().map(|script| {
+        if &script.code_hash() == dao_type_hash {
+            "dao"
+        } else {
+            "other"
+        }
+    })
+}
+
+/// Cell data is included when `data_limit` is given
+fn cell_json(
+    output: &CellOutput,
+    data: &Bytes,
+    data_limit: Option<usize>,
+    dao_type_hash: &Byte32,
+) -> serde_json::Value {
+    let capacity: u64 = output.capacity().unpack();
+    let data_hash: H256 = CellOutput::calc_data_hash(data).unpack();
+    let mut cell = json!({
+        "capacity": capacity,
+        "lock_hash": format!("{:#x}", output.lock().calc_script_hash()),
+        "type_hash": output.type_().to_opt().map(|script| format!("{:#x}", script.calc_script_hash())),
+        "data_hash": format!("{:#x}", data_hash),
+        "data_size": data.len(),
+        "type_kind": type_kind(output, dao_type_hash),
+    });
+    if type_kind(output, dao_type_hash) == Some("dao") {
+        cell["dao"] = json!({ "deposit_block_number": dao_deposit_block_number(data) });
+    }
+    if let Some(limit) = data_limit {
+        cell["data"] = json!(data_hex(data, limit));
+        if let Some(amount) = sudt_amount(output, data) {
+            // u128 does not fit in a json number
+            cell["sudt_amount"] = json!(amount.to_string());
+        }
+    }
+    cell
+}
+
+/// Summarize the mock transaction, cells not in mock info are loaded by `live_cell_getter`.
+/// The data of input/output cells is shown (truncated to `data_limit` bytes) if `data_limit` is given.
+/// `type_groups` lists the inputs and outputs of every type script (one script run each).
+pub fn inspect_tx<C>(
+    mock_tx: &MockTransaction,
+    expand_groups: bool,
+    data_limit: Option<usize>,
+    dao_type_hash: &Byte32,
+    mut live_cell_getter: C,
+) -> Result<serde_json::Value, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx = mock_tx.core_transaction();
+    let mut inputs = Vec::new();
+    // Type script hash => (script, input indices, output indices)
+    let mut type_groups: Vec<(Byte32, Script, Vec<usize>, Vec<usize>)> = Vec::new();
+    let mut add_to_type_group =
+        |output: &CellOutput, input_idx: Option<usize>, output_idx: Option<usize>| {
+            if let Some(script) = output.type_().to_opt() {
+                let hash = script.calc_script_hash();
+                let pos = match type_groups.iter().position(|group| group.0 == hash) {
+                    Some(pos) => pos,
+                    None => {
+                        type_groups.push((hash, script, Vec::new(), Vec::new()));
+                        type_groups.len() - 1
+                    }
+                };
+                type_groups[pos].2.extend(input_idx);
+                type_groups[pos].3.extend(output_idx);
+            }
+        };
+    for (idx, input) in tx.inputs().into_iter().enumerate() {
+        let since: u64 = input.since().unpack();
+        let cell_opt = mock_tx.get_input_cell(&input, &mut live_cell_getter)?;
+        if let Some((output, _)) = cell_opt.as_ref() {
+            add_to_type_group(output, Some(idx), None);
+        }
+        let cell =
+            cell_opt.map(|(output, data)| cell_json(&output, &data, data_limit, dao_type_hash));
+        inputs.push(json!({
+            "previous_output": out_point_json(&input.previous_output()),
+            "since": since,
+            "cell": cell,
+        }));
+    }
+    let mut outputs = Vec::new();
+    for (idx, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+        add_to_type_group(&output, None, Some(idx));
+        outputs.push(cell_json(&output, &data, data_limit, dao_type_hash));
+    }
+    let type_groups = type_groups
+        .into_iter()
+        .map(|(hash, script, input_indices, output_indices)| {
+            json!({
+                "type_hash": format!("{:#x}", hash),
+                "code_hash": format!("{:#x}", script.code_hash()),
+                "args": format!("0x{}", hex_string(&script.args().raw_data()).expect("hex")),
+                "inputs": input_indices,
+                "outputs": output_indices,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut cell_deps = Vec::new();
+    for cell_dep in tx.cell_deps().into_iter() {
+        let dep_type: DepType = cell_dep.dep_type().unpack();
+        let out_point = cell_dep.out_point();
+        let cell_opt = mock_tx.get_dep_cell(&out_point, &mut live_cell_getter)?;
+        let mut dep_json = json!({
+            "out_point": out_point_json(&out_point),
+            "dep_type": if dep_type == DepType::DepGroup { "dep_group" } else { "code" },
+            "cell": cell_opt.as_ref().map(|(output, data)| cell_json(output, data, None, dao_type_hash)),
+        });
+        if expand_groups && dep_type == DepType::DepGroup {
+            let (_, data) =
+                cell_opt.ok_or_else(|| format!("dep group cell not found: {}", out_point))?;
+            let mut sub_cells = Vec::new();
+            for sub_out_point in OutPointVec::from_slice(&data)
+                .map_err(|err| format!("Parse dep group data error: {}", err))?
+                .into_iter()
+            {
+                let cell = mock_tx
+                    .get_dep_cell(&sub_out_point, &mut live_cell_getter)?
+                    .map(|(output, data)| cell_json(&output, &data, None, dao_type_hash));
+                sub_cells.push(json!({
+                    "out_point": out_point_json(&sub_out_point),
+                    "cell": cell,
+                }));
+            }
+            dep_json["group_cells"] = json!(sub_cells);
+        }
+        cell_deps.push(dep_json);
+    }
+    let header_deps = tx
+        .header_deps()
+        .into_iter()
+        .map(|hash| format!("{:#x}", hash))
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "hash": format!("{:#x}", tx.hash()),
+        "inputs": inputs,
+        "outputs": outputs,
+        "cell_deps": cell_deps,
+        "header_deps": header_deps,
+        "type_groups": type_groups,
+        "witnesses_count": tx.witnesses().len(),
+    }))
+}
+
+/// Group inputs by lock script (inputs in a group are signed together), `key_available` is
+/// null for locks other than the secp256k1 lock.
+pub fn lock_groups<C>(
+    mock_tx: &MockTransaction,
+    secp_type_hash: &Byte32,
+    controlled_lock_args: &HashSet<H160>,
+    mut live_cell_getter: C,
+) -> Result<serde_json::Value, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx = mock_tx.core_transaction();
+    let mut groups: Vec<(Script, Vec<usize>)> = Vec::new();
+    for (idx, input) in tx.inputs().into_iter().enumerate() {
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        let lock = output.lock();
+        match groups.iter_mut().find(|(script, _)| script == &lock) {
+            Some((_, indices)) => indices.push(idx),
+            None => groups.push((lock, vec![idx])),
+        }
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(lock, indices)| {
+            let hash_type: ScriptHashType = lock.hash_type().unpack();
+            let args = lock.args().raw_data();
+            let key_available =
+                if hash_type == ScriptHashType::Type && &lock.code_hash() == secp_type_hash {
+                    H160::from_slice(&args)
+                        .ok()
+                        .map(|lock_arg| controlled_lock_args.contains(&lock_arg))
+                } else {
+                    None
+                };
+            json!({
+                "lock_hash": format!("{:#x}", lock.calc_script_hash()),
+                "lock_arg": format!("0x{}", hex_string(&args).expect("hex")),
+                "inputs": indices,
+                "key_available": key_available,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(json!(groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_hex() {
+        assert_eq!(data_hex(&[0x12, 0x34], 2), "0x1234");
+        assert_eq!(data_hex(&[0x12, 0x34, 0x56], 2), "0x1234...");
+        assert_eq!(data_hex(&[], 2), "0x");
+    }
+
+    #[test]
+    fn test_dao_deposit_block_number() {
+        assert_eq!(dao_deposit_block_number(&[]), None);
+        assert_eq!(dao_deposit_block_number(&[0u8; 8]), None);
+        assert_eq!(dao_deposit_block_number(&1024u64.to_le_bytes()), Some(1024));
+        assert_eq!(dao_deposit_block_number(&[1u8; 16]), None);
+    }
+}
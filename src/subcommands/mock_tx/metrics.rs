@@ -0,0 +1,206 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Upper bounds of the cycles histogram buckets, the last one is the max block cycles
+const CYCLES_BUCKETS: &[u64] = &[100_000, 1_000_000, 10_000_000, 100_000_000, 3_500_000_000];
+/// Upper bounds (seconds) of the per transaction duration histogram buckets
+const DURATION_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 5.0, 10.0, 30.0];
+
+/// Metrics of a batch command (verify-all/send-all), written in Prometheus text format
+#[derive(Debug, Clone)]
+pub struct BatchMetrics {
+    command: &'static str,
+    passed: usize,
+    failed: usize,
+    /// Cycles of every passed transaction
+    cycles: Vec<u64>,
+    /// Time spent on every transaction, passed or failed
+    durations: Vec<Duration>,
+    total_duration: Duration,
+}
+
+impl BatchMetrics {
+    pub fn new(command: &'static str) -> BatchMetrics {
+        BatchMetrics {
+            command,
+            passed: 0,
+            failed: 0,
+            cycles: Vec::new(),
+            durations: Vec::new(),
+            total_duration: Duration::default(),
+        }
+    }
+
+    pub fn record(&mut self, cycles: Option<u64>, duration: Duration) {
+        match cycles {
+            Some(cycles) => {
+                self.passed += 1;
+                self.cycles.push(cycles);
+            }
+            None => self.failed += 1,
+        }
+        self.durations.push(duration);
+    }
+
+    pub fn finish(&mut self, total_duration: Duration) {
+        self.total_duration = total_duration;
+    }
+
+    pub fn render(&self) -> String {
+        let label = format!("command=\"{}\"", self.command);
+        let mut out = String::new();
+        writeln!(
+            out,
+            "# HELP ckb_cli_mock_tx_batch_transactions Transactions processed by the batch"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ckb_cli_mock_tx_batch_transactions gauge").unwrap();
+        for (result, count) in &[("passed", self.passed), ("failed", self.failed)] {
+            writeln!(
+                out,
+                "ckb_cli_mock_tx_batch_transactions{{{},result=\"{}\"}} {}",
+                label, result, count
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "# HELP ckb_cli_mock_tx_cycles Cycles consumed by the passed transactions"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ckb_cli_mock_tx_cycles histogram").unwrap();
+        for bound in CYCLES_BUCKETS {
+            let count = self.cycles.iter().filter(|cycles| *cycles <= bound).count();
+            writeln!(
+                out,
+                "ckb_cli_mock_tx_cycles_bucket{{{},le=\"{}\"}} {}",
+                label, bound, count
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_cycles_bucket{{{},le=\"+Inf\"}} {}",
+            label,
+            self.cycles.len()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_cycles_sum{{{}}} {}",
+            label,
+            self.cycles.iter().sum::<u64>()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_cycles_count{{{}}} {}",
+            label,
+            self.cycles.len()
+        )
+        .unwrap();
+
+        let seconds = self
+            .durations
+            .iter()
+            .map(|duration| as_secs(*duration))
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "# HELP ckb_cli_mock_tx_duration_seconds Time spent on each transaction"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ckb_cli_mock_tx_duration_seconds histogram").unwrap();
+        for bound in DURATION_BUCKETS {
+            let count = seconds.iter().filter(|secs| *secs <= bound).count();
+            writeln!(
+                out,
+                "ckb_cli_mock_tx_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                label, bound, count
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            label,
+            seconds.len()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_duration_seconds_sum{{{}}} {}",
+            label,
+            seconds.iter().sum::<f64>()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_duration_seconds_count{{{}}} {}",
+            label,
+            seconds.len()
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "# HELP ckb_cli_mock_tx_batch_duration_seconds Wall-clock time of the whole batch"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ckb_cli_mock_tx_batch_duration_seconds gauge").unwrap();
+        writeln!(
+            out,
+            "ckb_cli_mock_tx_batch_duration_seconds{{{}}} {}",
+            label,
+            as_secs(self.total_duration)
+        )
+        .unwrap();
+        out
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.render())
+            .map_err(|err| format!("Write metrics file {:?} error: {}", path, err))
+    }
+}
+
+fn as_secs(duration: Duration) -> f64 {
+    duration.as_millis() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let mut metrics = BatchMetrics::new("verify-all");
+        metrics.record(Some(500_000), Duration::from_millis(200));
+        metrics.record(Some(20_000_000), Duration::from_millis(2000));
+        metrics.record(None, Duration::from_millis(50));
+        metrics.finish(Duration::from_millis(2250));
+        let text = metrics.render();
+        assert!(text.contains(
+            "ckb_cli_mock_tx_batch_transactions{command=\"verify-all\",result=\"passed\"} 2"
+        ));
+        assert!(text.contains(
+            "ckb_cli_mock_tx_batch_transactions{command=\"verify-all\",result=\"failed\"} 1"
+        ));
+        assert!(
+            text.contains("ckb_cli_mock_tx_cycles_bucket{command=\"verify-all\",le=\"1000000\"} 1")
+        );
+        assert!(
+            text.contains("ckb_cli_mock_tx_cycles_bucket{command=\"verify-all\",le=\"+Inf\"} 2")
+        );
+        assert!(text.contains("ckb_cli_mock_tx_cycles_sum{command=\"verify-all\"} 20500000"));
+        assert!(text.contains(
+            "ckb_cli_mock_tx_duration_seconds_bucket{command=\"verify-all\",le=\"0.5\"} 2"
+        ));
+        assert!(
+            text.contains("ckb_cli_mock_tx_batch_duration_seconds{command=\"verify-all\"} 2.25")
+        );
+    }
+}
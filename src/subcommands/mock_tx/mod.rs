@@ -0,0 +1,4792 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use ckb_jsonrpc_types::{
+    BlockNumber, BlockView as RpcBlockView, JsonBytes, Transaction as RpcTransaction,
+};
+use ckb_sdk::{
+    estimate_fee, sum_capacities, wallet::KeyStore, Address, GenesisInfo, HttpRpcClient,
+    MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction, MockTransactionHelper,
+    NetworkType, ReprMockTransaction, ScriptGroupKind, Since, SinceMetric,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        capacity_bytes, Capacity, DepType, EpochNumberWithFraction, HeaderBuilder, HeaderView,
+        ScriptHashType, TransactionBuilder,
+    },
+    h256,
+    packed::{CellDep, CellInput, CellOutput, OutPoint, OutPointVec, Script, Transaction},
+    prelude::*,
+    H160, H256,
+};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use flate2::read::GzDecoder;
+use rand::{rngs::StdRng, SeedableRng};
+use url::Url;
+
+mod bundle;
+mod capacity;
+mod collector;
+mod dao;
+mod events;
+mod explain;
+mod fuzz;
+mod graph;
+mod inspect;
+mod metrics;
+mod molecule;
+mod output;
+mod preserve;
+mod signing;
+mod warnings;
+
+use self::bundle::OfflineLoader;
+use self::collector::{CellCollector, CollectStrategy};
+use self::events::EventEmitter;
+use self::metrics::BatchMetrics;
+use self::output::{OutputTarget, OutputTargetParser};
+use self::warnings::Warnings;
+use super::CliSubCommand;
+use crate::utils::{
+    arg_parser::{
+        AddressParser, ArgParser, Base64Parser, CapacityParser, DirPathParser, EpochParser,
+        FilePathParser, FixedHashParser, FromStrParser, HexParser, OutPointParser, UrlParser,
+    },
+    other::{
+        check_node_sync_state, get_genesis_info, get_node_sync_state, get_singer, NodeSyncState,
+        PubkeyFormat,
+    },
+    printer::{OutputFormat, Printable},
+};
+
+/// Size of a recoverable secp256k1 signature witness
+const SECP_SIGNATURE_SIZE: usize = 65;
+/// Max block bytes of the consensus, a larger transaction never fits in a block
+const MAX_TX_SIZE: &str = "597000";
+/// Max block cycles of the consensus
+const MAX_BLOCK_CYCLES: &str = "3500000000";
+/// Upper limit of `verify-all --jobs`, each job has its own connection to the node
+const MAX_JOBS: usize = 16;
+
+pub struct MockTxSubCommand<'a> {
+    rpc_client: &'a mut HttpRpcClient,
+    key_store: &'a mut KeyStore,
+    genesis_info: Option<GenesisInfo>,
+    fee_rate: Option<u64>,
+    /// Addresses of other networks are refused if set
+    network: Option<NetworkType>,
+    /// Url of the node `rpc_client` connected to
+    rpc_url: String,
+    events: EventEmitter,
+    warnings: Warnings,
+    /// The node's sync state is checked once per command
+    sync_checked: bool,
+    /// Cell dep changes of the last completion
+    dep_changes: DepChanges,
+    /// Cells loaded by the loaders of the current command
+    resolved_cells: ResolvedCells,
+}
+
+impl<'a> MockTxSubCommand<'a> {
+    pub fn new(
+        rpc_client: &'a mut HttpRpcClient,
+        key_store: &'a mut KeyStore,
+        genesis_info: Option<GenesisInfo>,
+        fee_rate: Option<u64>,
+        network: Option<NetworkType>,
+        rpc_url: String,
+    ) -> MockTxSubCommand<'a> {
+        MockTxSubCommand {
+            rpc_client,
+            key_store,
+            genesis_info,
+            fee_rate,
+            network,
+            rpc_url,
+            events: EventEmitter::default(),
+            warnings: Warnings::default(),
+            sync_checked: false,
+            dep_changes: DepChanges::default(),
+            resolved_cells: ResolvedCells::default(),
+        }
+    }
+
+    pub fn subcommand(name: &'static str) -> App<'static, 'static> {
+        let arg_tx_file = Arg::with_name("tx-file")
+            .long("tx-file")
+            .takes_value(true)
+            .required(true)
+            .validator(|input| {
+                if is_url(input) {
+                    UrlParser.validate(input)
+                } else {
+                    FilePathParser::new(true).validate(input)
+                }
+            })
+            .help(
+                "Mock transaction data file or http(s) url (format: json/yaml/json5, gzip compressed if ends with .gz)",
+            );
+        let arg_tx_hex = Arg::with_name("tx-hex")
+            .long("tx-hex")
+            .takes_value(true)
+            .conflicts_with("tx-file")
+            .validator(|input| HexParser.validate(input))
+            .help("Molecule serialized transaction (hex string), inputs and deps are resolved from the node");
+        let arg_tx_base64 = Arg::with_name("tx-base64")
+            .long("tx-base64")
+            .takes_value(true)
+            .conflicts_with_all(&["tx-file", "tx-hex"])
+            .validator(|input| Base64Parser.validate(input))
+            .help("Molecule serialized transaction (base64, as printed by `complete --base64-out`), inputs and deps are resolved from the node");
+        let arg_output_file = Arg::with_name("output-file")
+            .long("output-file")
+            .takes_value(true)
+            .validator(|input| OutputTargetParser.validate(input))
+            .help("Completed mock transaction data file (format: json, gzip compressed if ends with .gz), also support `file://<path>`, `http(s)://<url>` (POST) and `clipboard:`");
+        let arg_data_store = Arg::with_name("data-store")
+            .long("data-store")
+            .takes_value(true)
+            .validator(|input| DirPathParser::new(true).validate(input))
+            .help("Directory of cell data files named by data hash, the code of `data` hash type scripts is loaded from here instead of the node");
+        let arg_fee_rate = Arg::with_name("fee-rate")
+            .long("fee-rate")
+            .takes_value(true)
+            .validator(|input| FromStrParser::<u64>::default().validate(input))
+            .help("Fee rate (shannons/KB) deducted from the change output [default: fee_rate in config file, or 0]");
+        let arg_pubkey_format = Arg::with_name("pubkey-format")
+            .long("pubkey-format")
+            .takes_value(true)
+            .possible_values(&["compressed", "uncompressed"])
+            .default_value("compressed")
+            .help("The public key format used to derive the lock arg when signing (uncompressed key must be unlocked)");
+        let arg_use_keyring = Arg::with_name("use-keyring")
+            .long("use-keyring")
+            .help("Take the key store password from the OS keyring (service `ckb-cli`, account: lock arg), prompt for it if there is no entry");
+        let arg_collect_strategy = Arg::with_name("collect-strategy")
+            .long("collect-strategy")
+            .takes_value(true)
+            .possible_values(&[
+                "minimize-inputs",
+                "minimize-change",
+                "consolidate",
+                "largest-first",
+                "smallest-first",
+            ])
+            .default_value("minimize-inputs")
+            .help("How live cells are picked to cover the capacity: minimize-inputs (largest first, lowest fee), minimize-change (least capacity left for change), consolidate (smallest first, reduces wallet fragmentation at a higher fee)");
+        let arg_change_lock_arg = Arg::with_name("change-lock-arg")
+            .long("change-lock-arg")
+            .takes_value(true)
+            .validator(|input| FixedHashParser::<H160>::default().validate(input))
+            .help("The lock_arg of the change output (secp256k1 lock) [default: lock of the first input, which must be in key store]");
+        let arg_max_inputs = Arg::with_name("max-inputs")
+            .long("max-inputs")
+            .takes_value(true)
+            .default_value("256")
+            .validator(|input| FromStrParser::<usize>::default().validate(input))
+            .help("Abort before resolving inputs if the transaction has more inputs than this");
+        let arg_max_tx_size = Arg::with_name("max-tx-size")
+            .long("max-tx-size")
+            .takes_value(true)
+            .default_value(MAX_TX_SIZE)
+            .validator(|input| FromStrParser::<u64>::default().validate(input))
+            .help("Abort if the completed transaction is larger than this (bytes), the default is the max block bytes");
+        let arg_at_block = Arg::with_name("at-block")
+            .long("at-block")
+            .takes_value(true)
+            .validator(|input| {
+                input
+                    .parse::<u64>()
+                    .map(|_| ())
+                    .or_else(|_| FixedHashParser::<H256>::default().validate(input))
+            })
+            .help("Resolve cells as of this block (number or hash), cells created after it are treated as not exists, spent status is not checked");
+        let arg_output_data_stdin = Arg::with_name("output-data-stdin")
+            .long("output-data-stdin")
+            .takes_value(true)
+            .value_name("output-index")
+            .validator(|input| FromStrParser::<usize>::default().validate(input))
+            .help(
+                "Read the cell data (binary) from stdin and attach it to the output at this index",
+            );
+        let arg_sign_indices = Arg::with_name("sign-indices")
+            .long("sign-indices")
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .validator(|input| FromStrParser::<usize>::default().validate(input))
+            .help("Only sign the inputs at these indices (e.g. 0,2,5), other witnesses are left for other signers [default: all inputs the key store can sign]");
+        let arg_metrics_out = Arg::with_name("metrics-out")
+            .long("metrics-out")
+            .takes_value(true)
+            .validator(|input| FilePathParser::new(false).validate(input))
+            .help("Write metrics of the batch (transaction counts, cycles and duration histograms) to this file in Prometheus text format");
+        let arg_preserve_format = Arg::with_name("preserve-format")
+            .long("preserve-format")
+            .help("Keep the formatting and comments of a YAML --tx-file by only patching the changed values, fall back to re-rendering (with a warning) when items are added or removed");
+        let arg_lock_arg = Arg::with_name("lock-arg")
+            .long("lock-arg")
+            .takes_value(true)
+            .validator(|input| FixedHashParser::<H160>::default().validate(input))
+            .required(true)
+            .help("The lock_arg (identifier) of the account");
+        SubCommand::with_name(name)
+            .about("Handle mock transactions (verify/send)")
+            .arg(
+                Arg::with_name("json-events")
+                    .long("json-events")
+                    .global(true)
+                    .help("Emit newline-delimited JSON progress events to stderr"),
+            )
+            .arg(
+                Arg::with_name("strict")
+                    .long("strict")
+                    .global(true)
+                    .help("Treat warnings as errors"),
+            )
+            .arg(
+                Arg::with_name("download-timeout")
+                    .long("download-timeout")
+                    .global(true)
+                    .takes_value(true)
+                    .default_value("30")
+                    .validator(|input| FromStrParser::<u64>::default().validate(input))
+                    .help("Timeout (seconds) of downloading a --tx-file url"),
+            )
+            .arg(
+                Arg::with_name("capacity-unit")
+                    .long("capacity-unit")
+                    .global(true)
+                    .takes_value(true)
+                    .possible_values(&["shannon", "ckb"])
+                    .help("Unit of capacities in the written mock transaction, `ckb` writes them as \"<amount> CKB (<shannons> shannons)\" [default: shannon (hex)]"),
+            )
+            .arg(
+                Arg::with_name("shannons-only")
+                    .long("shannons-only")
+                    .global(true)
+                    .conflicts_with("capacity-unit")
+                    .help("Always write capacities as raw shannons"),
+            )
+            .arg(
+                Arg::with_name("strict-parse")
+                    .long("strict-parse")
+                    .global(true)
+                    .help("Reject unknown fields in mock transaction files"),
+            )
+            .subcommands(vec![
+                SubCommand::with_name("template")
+                    .about("Print mock transaction template")
+                    .arg(arg_lock_arg.clone().required(false))
+                    .arg(
+                        Arg::with_name("lock-code-hash")
+                            .long("lock-code-hash")
+                            .takes_value(true)
+                            .conflicts_with_all(&["lock-arg", "from-address"])
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .help("Code hash of a custom lock used by the sample scripts [default: secp256k1 lock]"),
+                    )
+                    .arg(
+                        Arg::with_name("lock-hash-type")
+                            .long("lock-hash-type")
+                            .takes_value(true)
+                            .requires("lock-code-hash")
+                            .possible_values(&["data", "type"])
+                            .help("Hash type of the custom lock [default: type]"),
+                    )
+                    .arg(
+                        Arg::with_name("lock-args")
+                            .long("lock-args")
+                            .takes_value(true)
+                            .requires("lock-code-hash")
+                            .validator(|input| HexParser.validate(input))
+                            .help("Args of the custom lock (hex string)"),
+                    )
+                    .arg(
+                        Arg::with_name("from-address")
+                            .long("from-address")
+                            .takes_value(true)
+                            .validator(|input| AddressParser.validate(input))
+                            .help("Prefill the inputs with live cells of this address, requires --capacity or --sweep (the lock hash must be indexed by the node)"),
+                    )
+                    .arg(arg_collect_strategy.clone().requires("from-address"))
+                    .arg(
+                        Arg::with_name("sweep")
+                            .long("sweep")
+                            .requires_all(&["from-address", "to-lock-arg"])
+                            .conflicts_with_all(&["capacity", "lock-arg", "lock-code-hash", "cell-dep-type"])
+                            .help("Consolidate all live cells of --from-address into one output of --to-lock-arg, the fee is left to complete"),
+                    )
+                    .arg(
+                        Arg::with_name("to-lock-arg")
+                            .long("to-lock-arg")
+                            .takes_value(true)
+                            .requires("sweep")
+                            .validator(|input| FixedHashParser::<H160>::default().validate(input))
+                            .help("The lock_arg of the sweep output (secp256k1 lock)"),
+                    )
+                    .arg(arg_fee_rate.clone().requires("sweep"))
+                    .arg(
+                        Arg::with_name("capacity")
+                            .long("capacity")
+                            .takes_value(true)
+                            .requires("from-address")
+                            .validator(|input| CapacityParser.validate(input))
+                            .help("The output capacity, the prefilled inputs must cover it (unit: CKB, format: 123.335)"),
+                    )
+                    .arg(
+                        Arg::with_name("cell-dep-type")
+                            .long("cell-dep-type")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .possible_values(&["code", "dep_group"])
+                            .help("Add a placeholder cell dep of this dep_type (repeatable, one cell dep each)"),
+                    )
+                    .arg(arg_output_data_stdin.clone())
+                    .arg(
+                        Arg::with_name("genesis-file")
+                            .long("genesis-file")
+                            .takes_value(true)
+                            .conflicts_with("from-address")
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .help("Genesis block file (json/yaml, saved by `rpc get_block_by_number --number 0`), system scripts are taken from it instead of the node so no node is needed"),
+                    )
+                    .arg(arg_output_file.clone().help("Save to a output file")),
+                SubCommand::with_name("complete")
+                    .about("Complete the mock transaction")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_at_block.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone())
+                    .arg(arg_max_tx_size.clone())
+                    .arg(
+                        Arg::with_name("deduplicate-deps")
+                            .long("deduplicate-deps")
+                            .help("Remove duplicate cell deps (same out point and dep_type) before completing"),
+                    )
+                    .arg(
+                        Arg::with_name("annotate-inputs")
+                            .long("annotate-inputs")
+                            .conflicts_with("emit-rpc-request")
+                            .help("Add the resolved capacity and lock of each input next to it (an informational `_resolved` field, not part of the transaction)"),
+                    )
+                    .arg(
+                        Arg::with_name("emit-rpc-request")
+                            .long("emit-rpc-request")
+                            .help("Output a send_transaction JSON-RPC request body of the completed transaction instead of the mock transaction"),
+                    )
+                    .arg(
+                        Arg::with_name("base64-out")
+                            .long("base64-out")
+                            .conflicts_with_all(&["emit-rpc-request", "annotate-inputs"])
+                            .help("Output the molecule serialized completed transaction as URL-safe base64 instead of the mock transaction"),
+                    )
+                    .arg(
+                        Arg::with_name("prune-deps")
+                            .long("prune-deps")
+                            .help("Report cell deps not providing the code of any input lock/type or output type script, they are kept as a script may load them as data"),
+                    )
+                    .arg(
+                        Arg::with_name("no-data-deps")
+                            .long("no-data-deps")
+                            .requires("prune-deps")
+                            .help("Confirm no script loads a cell dep as data, the cell deps reported by --prune-deps are removed"),
+                    )
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Completed mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("verify")
+                    .about("Verify a mock transaction in local")
+                    .arg(arg_tx_file.clone().required_unless_one(&["tx-hex", "tx-base64"]))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_tx_base64.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_at_block.clone().conflicts_with("cells-file"))
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone())
+                    .arg(
+                        Arg::with_name("cells-file")
+                            .long("cells-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .help("Mock transaction file (e.g. made by `mock-tx bundle`) providing the cells and headers, verify without loading them from node"),
+                    )
+                    .arg(
+                        Arg::with_name("genesis-file")
+                            .long("genesis-file")
+                            .takes_value(true)
+                            .requires("cells-file")
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .help("Genesis block file (json/yaml, saved by `rpc get_block_by_number --number 0`), system scripts are taken from it so no node is needed with --cells-file"),
+                    )
+                    .arg(
+                        Arg::with_name("no-complete")
+                            .long("no-complete")
+                            .conflicts_with_all(&["fee-rate", "change-lock-arg", "sign-indices"])
+                            .help("Verify the transaction as written (no change output, cell deps or signatures added), fail if it is not complete"),
+                    )
+                    .arg(
+                        Arg::with_name("skip-header-resolution")
+                            .long("skip-header-resolution")
+                            .help("Do not resolve header deps from node, only headers in the mock transaction file are used"),
+                    )
+                    .arg(
+                        Arg::with_name("current-block")
+                            .long("current-block")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Check inputs' absolute since (block number) against this block number"),
+                    )
+                    .arg(
+                        Arg::with_name("current-epoch")
+                            .long("current-epoch")
+                            .takes_value(true)
+                            .validator(|input| EpochParser.validate(input))
+                            .help("Check inputs' absolute since (epoch) against this epoch, with the fraction reached in it: {number}[.{index}/{length}], e.g. 10.1/2"),
+                    )
+                    .arg(
+                        Arg::with_name("check-relative-since")
+                            .long("check-relative-since")
+                            .conflicts_with("cells-file")
+                            .help("Check inputs' relative since (block number or epoch) against the node's tip, the creation blocks of the inputs are fetched from the node"),
+                    )
+                    .arg(
+                        Arg::with_name("max-cycles")
+                            .long("max-cycles")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Fail the verification if total cycles of all script groups exceed this limit"),
+                    )
+                    .arg(
+                        Arg::with_name("per-group-max-cycles")
+                            .long("per-group-max-cycles")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Fail the verification if any single script group consumes more cycles, the group is reported"),
+                    )
+                    .arg(
+                        Arg::with_name("allowed-code-hash")
+                            .long("allowed-code-hash")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .help("Fail if any lock/type script of the inputs and outputs uses a code hash not in this list (repeatable)"),
+                    )
+                    .arg(
+                        Arg::with_name("show-timing")
+                            .long("show-timing")
+                            .help("Report wall-clock time (milliseconds) of resolving inputs, filling deps, signing and verifying"),
+                    )
+                    .arg(
+                        Arg::with_name("only-scripts")
+                            .long("only-scripts")
+                            .takes_value(true)
+                            .possible_values(&["lock", "type"])
+                            .help("Verify only the lock or the type script groups (e.g. skip placeholder signatures while working on a type script), not a full acceptance check"),
+                    )
+                    .arg(
+                        Arg::with_name("continue-on-script-error")
+                            .long("continue-on-script-error")
+                            .help("Verify every script group independently and report all the failing groups, instead of stopping at the first one"),
+                    )
+                    .arg(
+                        Arg::with_name("snapshot-mismatch-ok")
+                            .long("snapshot-mismatch-ok")
+                            .conflicts_with("cells-file")
+                            .help("If a cell resolved by the command is not live any more (e.g. the node is reorganizing), resolve and verify once more before failing"),
+                    )
+                    .arg(
+                        Arg::with_name("check-dao")
+                            .long("check-dao")
+                            .help("Recompute the maximum withdraw (deposit plus compensation) of Nervos DAO withdrawing inputs, fail if the outputs take more"),
+                    ),
+                SubCommand::with_name("bundle")
+                    .about("Complete and verify the mock transaction, then save it with all the cells and headers it depends on (replayable offline by `verify --cells-file`)")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_at_block.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone())
+                    .arg(arg_output_file.clone().help("Bundle file (format: json)")),
+                SubCommand::with_name("rebase")
+                    .about("Replace the inputs not live any more with live cells of an address, then complete the transaction (use on templates, not completed transactions)")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("address")
+                            .long("address")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| AddressParser.validate(input))
+                            .help("Address of the new input cells (the lock hash must be indexed by the node)"),
+                    )
+                    .arg(arg_collect_strategy.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Completed mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("verify-all")
+                    .about("Verify all mock transaction files in a directory")
+                    .arg(
+                        Arg::with_name("dir")
+                            .long("dir")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| DirPathParser::new(true).validate(input))
+                            .help("Directory of mock transaction files (.json/.yaml/.json5, optionally .gz)"),
+                    )
+                    .arg(arg_data_store.clone())
+                    .arg(
+                        Arg::with_name("max-cycles")
+                            .long("max-cycles")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Fail the verification if the transaction consumes more cycles"),
+                    )
+                    .arg(
+                        Arg::with_name("count-only")
+                            .long("count-only")
+                            .help("Only print passed/total"),
+                    )
+                    .arg(
+                        Arg::with_name("jobs")
+                            .long("jobs")
+                            .takes_value(true)
+                            .default_value("1")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("Verify this many files concurrently, each with its own node connection (at most 16)"),
+                    )
+                    .arg(arg_metrics_out.clone()),
+                SubCommand::with_name("send-all")
+                    .about("Complete then send the transactions of all mock transaction files in a directory (in file name order)")
+                    .arg(
+                        Arg::with_name("dir")
+                            .long("dir")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| DirPathParser::new(true).validate(input))
+                            .help("Directory of mock transaction files (.json/.yaml/.json5, optionally .gz)"),
+                    )
+                    .arg(arg_data_store.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_max_tx_size.clone())
+                    .arg(
+                        Arg::with_name("keep-going")
+                            .long("keep-going")
+                            .help("Continue with the remaining files when one fails [default: abort on the first failure]"),
+                    )
+                    .arg(arg_metrics_out.clone())
+                    .arg(
+                        Arg::with_name("force")
+                            .long("force")
+                            .help("Send even if the node is not synced to the network tip"),
+                    ),
+                SubCommand::with_name("canonicalize")
+                    .about("Sort mock cell deps and header deps into a canonical order")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_output_data_stdin.clone())
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Canonical mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("inspect")
+                    .about("Show the summary of a mock transaction")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(
+                        Arg::with_name("expand-groups")
+                            .long("expand-groups")
+                            .help("Resolve dep_group cell deps and list the cells in the group"),
+                    )
+                    .arg(
+                        Arg::with_name("show-data")
+                            .long("show-data")
+                            .help("Show the data (hex) of input and output cells, and the amount of sUDT cells"),
+                    )
+                    .arg(
+                        Arg::with_name("data-limit")
+                            .long("data-limit")
+                            .takes_value(true)
+                            .default_value("32")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("Only show the first bytes of the data longer than this (with --show-data)"),
+                    )
+                    .arg(
+                        Arg::with_name("full-data")
+                            .long("full-data")
+                            .requires("show-data")
+                            .help("Show the full data (with --show-data)"),
+                    ),
+                SubCommand::with_name("groups")
+                    .about("Group inputs by lock script (signed together), show whether the key is in key store")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone()),
+                SubCommand::with_name("set-witness")
+                    .about("Replace the witness at given index (without re-signing)")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("index")
+                            .long("index")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("The witness index"),
+                    )
+                    .arg(
+                        Arg::with_name("witness")
+                            .long("witness")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| HexParser.validate(input))
+                            .help("The witness data (hex)"),
+                    )
+                    .arg(arg_output_data_stdin.clone())
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Modified mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("hash")
+                    .about("Print the canonical transaction hash of a mock transaction")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone()),
+                SubCommand::with_name("witness-hash")
+                    .about("Print the witness hash (transaction hash including witnesses) of a mock transaction")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone()),
+                SubCommand::with_name("header-deps")
+                    .about("List the header deps with their block number and epoch, flag those neither in the file nor known by the node")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone()),
+                SubCommand::with_name("compare-chain")
+                    .about("Check whether the transaction is on chain (or in the tx-pool) with the same serialized bytes")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone()),
+                SubCommand::with_name("script-hash")
+                    .about("Print the secp256k1 lock script hash of a lock arg or an address")
+                    .arg(arg_lock_arg.clone().required_unless("address"))
+                    .arg(
+                        Arg::with_name("address")
+                            .long("address")
+                            .takes_value(true)
+                            .conflicts_with("lock-arg")
+                            .validator(|input| AddressParser.validate(input))
+                            .help("The address of the account"),
+                    ),
+                SubCommand::with_name("since")
+                    .about("Print the packed `since` value (hex) of an input time lock")
+                    .arg(
+                        Arg::with_name("block")
+                            .long("block")
+                            .takes_value(true)
+                            .required_unless("epoch")
+                            .conflicts_with("epoch")
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Lock until this block number (or this many blocks after the input cell is committed, with --relative)"),
+                    )
+                    .arg(
+                        Arg::with_name("epoch")
+                            .long("epoch")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Lock until this epoch number (or this many epochs after the input cell is committed, with --relative)"),
+                    )
+                    .arg(
+                        Arg::with_name("relative")
+                            .long("relative")
+                            .help("Relative to the block committing the input cell [default: absolute]"),
+                    ),
+                SubCommand::with_name("graph")
+                    .about("Export the transaction structure as a graph (render with `dot -Tpng`)")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(
+                        Arg::with_name("format")
+                            .long("format")
+                            .takes_value(true)
+                            .possible_values(&["dot"])
+                            .default_value("dot")
+                            .help("Graph format"),
+                    ),
+                SubCommand::with_name("molecule")
+                    .about("Show the molecule layout (offsets and sizes) of the serialized transaction")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone()),
+                SubCommand::with_name("export-witnesses")
+                    .about("Print the witnesses of a mock transaction (json array of hex strings)")
+                    .arg(arg_tx_file.clone()),
+                SubCommand::with_name("import-witnesses")
+                    .about("Replace all witnesses of a mock transaction")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("witnesses")
+                            .long("witnesses")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .help("Witnesses file (json array of hex strings, as printed by export-witnesses)"),
+                    )
+                    .arg(arg_output_data_stdin.clone())
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Modified mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("replace-dep")
+                    .about("Replace the out point of matching cell deps (e.g. the script is redeployed)")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("old")
+                            .long("old")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| OutPointParser.validate(input))
+                            .help("The out point to replace (format: {tx-hash}-{index})"),
+                    )
+                    .arg(
+                        Arg::with_name("new")
+                            .long("new")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| OutPointParser.validate(input))
+                            .help("The new out point (format: {tx-hash}-{index})"),
+                    )
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Modified mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("replace-lock")
+                    .about("Replace the lock of outputs with a secp256k1 lock of a new lock arg, capacity and data are kept")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("index")
+                            .long("index")
+                            .takes_value(true)
+                            .multiple(true)
+                            .use_delimiter(true)
+                            .required_unless("all-outputs")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("Indices of the outputs to replace the lock (e.g. 0,2)"),
+                    )
+                    .arg(
+                        Arg::with_name("all-outputs")
+                            .long("all-outputs")
+                            .conflicts_with("index")
+                            .help("Replace the lock of every output"),
+                    )
+                    .arg(arg_lock_arg.clone().help("The lock_arg of the new secp256k1 lock"))
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Modified mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("reorder-outputs")
+                    .about("Reorder the outputs (and outputs data) by a permutation of the output indices")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        Arg::with_name("order")
+                            .long("order")
+                            .takes_value(true)
+                            .required(true)
+                            .multiple(true)
+                            .use_delimiter(true)
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("The output indices in new order (e.g. 2,0,1 moves output 2 to the front)"),
+                    )
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Modified mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("signing-request")
+                    .about("Print the message to sign and the inputs of each secp256k1 lock arg (for an offline signer)")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_data_store.clone()),
+                SubCommand::with_name("sign-hashes")
+                    .about("Print the message hash each secp256k1 lock arg group signs, without signing")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(
+                        Arg::with_name("dump-preimage")
+                            .long("dump-preimage")
+                            .help("Also print the exact bytes (hex) hashed into the message of each group"),
+                    ),
+                SubCommand::with_name("import-signatures")
+                    .about("Put signatures from an offline signer into the witnesses")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(
+                        Arg::with_name("signatures")
+                            .long("signatures")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .help("Signatures file (json object of lock_arg to signature, hex strings)"),
+                    )
+                    .arg(arg_preserve_format.clone())
+                    .arg(
+                        arg_output_file
+                            .clone()
+                            .help("Signed mock transaction data file (format: json)"),
+                    ),
+                SubCommand::with_name("fuzz")
+                    .about("Verify random mutations (witnesses, cell data) of a transaction, report those crashing the VM instead of failing the script cleanly")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(
+                        Arg::with_name("iterations")
+                            .long("iterations")
+                            .takes_value(true)
+                            .default_value("100")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .help("Number of mutations to verify"),
+                    )
+                    .arg(
+                        Arg::with_name("seed")
+                            .long("seed")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Seed of the random mutations, to reproduce a run [default: random]"),
+                    )
+                    .arg(
+                        Arg::with_name("max-cycles")
+                            .long("max-cycles")
+                            .takes_value(true)
+                            .default_value(MAX_BLOCK_CYCLES)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Max cycles of verifying a mutation, a script looping forever exceeds it"),
+                    ),
+                SubCommand::with_name("explain-error")
+                    .about("Explain a script exit code (system secp256k1 lock's meaning)")
+                    .arg(
+                        Arg::with_name("code")
+                            .long("code")
+                            .takes_value(true)
+                            .required(true)
+                            .allow_hyphen_values(true)
+                            .validator(|input| FromStrParser::<i8>::default().validate(input))
+                            .help("The script exit code"),
+                    ),
+                SubCommand::with_name("test-pool")
+                    .about("Complete the transaction, then ask the node whether its tx-pool would accept it (fee, size, orphan...) without submitting")
+                    .arg(arg_tx_file.clone().required_unless("tx-hex"))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone()),
+                SubCommand::with_name("send")
+                    .about("Complete then send a transaction")
+                    .arg(arg_tx_file.clone().required_unless_one(&["tx-hex", "tx-base64"]))
+                    .arg(arg_tx_hex.clone())
+                    .arg(arg_tx_base64.clone())
+                    .arg(arg_data_store.clone())
+                    .arg(arg_fee_rate.clone())
+                    .arg(arg_pubkey_format.clone())
+                    .arg(arg_use_keyring.clone())
+                    .arg(arg_change_lock_arg.clone())
+                    .arg(arg_max_inputs.clone())
+                    .arg(arg_sign_indices.clone())
+                    .arg(
+                        Arg::with_name("rpc-url")
+                            .long("rpc-url")
+                            .takes_value(true)
+                            .multiple(true)
+                            .number_of_values(1)
+                            .validator(|input| UrlParser.validate(input))
+                            .help("Send the transaction to this node (can be repeated, default: the connected node)"),
+                    )
+                    .arg(
+                        Arg::with_name("pre-send-hook")
+                            .long("pre-send-hook")
+                            .takes_value(true)
+                            .help("Pipe the completed transaction (json) to this command before send, the transaction printed by the command will be verified and sent"),
+                    )
+                    .arg(
+                        Arg::with_name("max-fee-rate")
+                            .long("max-fee-rate")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .help("Abort if the fee rate (shannons/KB) of the completed transaction exceeds this"),
+                    )
+                    .arg(
+                        Arg::with_name("verify-after-send")
+                            .long("verify-after-send")
+                            .help("Fetch the transaction back from the node after send and check it is exactly what was sent"),
+                    )
+                    .arg(
+                        Arg::with_name("broadcast-log")
+                            .long("broadcast-log")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .help("Append a json line (timestamp, tx-hash, fee, size, node) to this file for every broadcast"),
+                    )
+                    .arg(arg_max_tx_size.clone())
+                    .arg(
+                        Arg::with_name("interactive")
+                            .long("interactive")
+                            .help("Print a summary (inputs, outputs, fee, tx-hash) and ask for confirmation before send"),
+                    )
+                    .arg(
+                        Arg::with_name("force")
+                            .long("force")
+                            .help("Send even if the node is not synced to the network tip"),
+                    ),
+            ])
+    }
+
+    fn complete_tx(
+        &mut self,
+        m: &ArgMatches,
+        genesis_info: &GenesisInfo,
+        verify: bool,
+    ) -> Result<(MockTransaction, u64), String> {
+        let mock_tx = load_mock_tx(m)?;
+        self.complete_mock_tx(m, mock_tx, genesis_info, verify)
+    }
+
+    fn complete_mock_tx(
+        &mut self,
+        m: &ArgMatches,
+        mut mock_tx: MockTransaction,
+        genesis_info: &GenesisInfo,
+        verify: bool,
+    ) -> Result<(MockTransaction, u64), String> {
+        self.dep_changes = DepChanges::default();
+        let max_inputs: usize = FromStrParser::<usize>::default().from_matches(m, "max-inputs")?;
+        let inputs_len = mock_tx.tx.raw().inputs().len();
+        if inputs_len > max_inputs {
+            return Err(format!(
+                "Too many inputs: {} > {} (--max-inputs)",
+                inputs_len, max_inputs
+            ));
+        }
+        if m.is_present("deduplicate-deps") {
+            self.dep_changes.duplicates_removed = mock_tx.dedup_cell_deps();
+        }
+        if m.is_present("prune-deps") {
+            let mut loader = self.loader(m)?;
+            let unused = unused_cell_deps(&mock_tx, |out_point| loader.get_live_cell(out_point))?;
+            // Whether a script loads a cell dep as data is only known by running it
+            if m.is_present("no-data-deps") {
+                mock_tx.tx = remove_cell_deps(&mock_tx.tx, &unused);
+                self.dep_changes.unused_removed = unused.iter().map(cell_dep_desc).collect();
+            } else {
+                self.dep_changes.unused = unused.iter().map(cell_dep_desc).collect();
+            }
+        }
+        let fee_rate: u64 = FromStrParser::<u64>::default()
+            .from_matches_opt(m, "fee-rate", false)?
+            .or(self.fee_rate)
+            .unwrap_or(0);
+
+        // Nothing is loaded from the node with --cells-file
+        if !self.sync_checked && !m.is_present("cells-file") {
+            self.sync_checked = true;
+            if let Some(warning) = check_node_sync_state(self.rpc_client) {
+                self.warnings.raise(warning)?;
+            }
+        }
+        let pubkey_format: PubkeyFormat = FromStrParser::<PubkeyFormat>::new()
+            .from_matches_opt(m, "pubkey-format", false)?
+            .unwrap_or(PubkeyFormat::Compressed);
+        let signer = get_singer(
+            self.key_store.clone(),
+            pubkey_format,
+            m.is_present("use-keyring"),
+        );
+        let change_lock_arg: Option<H160> =
+            FixedHashParser::<H160>::default().from_matches_opt(m, "change-lock-arg", false)?;
+        let change_lock = change_lock_arg.as_ref().map(|lock_arg| {
+            Script::new_builder()
+                .code_hash(genesis_info.secp_type_hash().clone())
+                .hash_type(ScriptHashType::Type.pack())
+                .args(Bytes::from(lock_arg.as_ref()).pack())
+                .build()
+        });
+        let sign_indices: Option<HashSet<usize>> = if m.is_present("sign-indices") {
+            Some(
+                FromStrParser::<usize>::default()
+                    .from_matches_vec::<usize>(m, "sign-indices")?
+                    .into_iter()
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let key_lock_args: HashSet<H160> = self.key_store.get_accounts().keys().cloned().collect();
+        // Lock args the user controls, change output must go back to one of them
+        let mut controlled_lock_args = key_lock_args.clone();
+        controlled_lock_args.extend(change_lock_arg);
+        let mut added_deps = Vec::new();
+        let mut loader = self.loader(m)?;
+        {
+            let events = self.events.clone();
+            let inputs_count = mock_tx.core_transaction().inputs().len();
+            let scripts_count = inputs_count + mock_tx.core_transaction().outputs().len();
+            let mut helper = MockTransactionHelper::new(&mut mock_tx);
+            helper.check_outputs_capacity()?;
+            let change_capacity = events.phase("resolving-inputs", inputs_count, || {
+                helper.add_change_output_with_fee(change_lock, fee_rate, |out_point| {
+                    loader.get_live_cell(out_point)
+                })
+            })?;
+            if change_capacity > 0 {
+                let outputs = helper.mock_tx.tx.raw().outputs();
+                let change_output = outputs
+                    .get(outputs.len() - 1)
+                    .expect("change output exists");
+                check_change_lock(&change_output.lock(), genesis_info, &controlled_lock_args)?;
+            }
+            let added_cell_deps = events.phase("filling-deps", scripts_count, || {
+                helper.fill_deps(genesis_info, |out_point| loader.get_live_cell(out_point))
+            })?;
+            for cell_dep in added_cell_deps {
+                let name = if cell_dep == genesis_info.secp_dep() {
+                    "secp256k1"
+                } else if cell_dep == genesis_info.dao_dep() {
+                    "dao"
+                } else {
+                    continue;
+                };
+                let index: u32 = cell_dep.out_point().index().unpack();
+                added_deps.push(serde_json::json!({
+                    "name": name,
+                    "out-point": format!("{:#x}-{}", cell_dep.out_point().tx_hash(), index),
+                }));
+            }
+            if let Some(indices) = sign_indices.as_ref() {
+                check_sign_indices(
+                    helper.mock_tx,
+                    indices,
+                    genesis_info,
+                    &key_lock_args,
+                    |out_point| loader.get_live_cell(out_point),
+                )?;
+            }
+            events.phase("signing", inputs_count, || {
+                helper.fill_witnesses_opt(
+                    genesis_info,
+                    &signer,
+                    |out_point| loader.get_live_cell(out_point),
+                    sign_indices.as_ref(),
+                )
+            })?;
+        }
+        self.dep_changes.added = added_deps;
+        let max_tx_size: Option<u64> =
+            FromStrParser::<u64>::default().from_matches_opt(m, "max-tx-size", false)?;
+        if let Some(max_tx_size) = max_tx_size {
+            let size = tx_size(&mock_tx.tx);
+            if size > max_tx_size {
+                return Err(format!(
+                    "Transaction too large: {} bytes > {} (--max-tx-size)",
+                    size, max_tx_size
+                ));
+            }
+        }
+        let cycle = if verify {
+            self.verify_tx(m, &mut mock_tx)?
+        } else {
+            0
+        };
+        Ok((mock_tx, cycle))
+    }
+
+    /// Complete (unless --no-complete) and verify the transaction, cells are resolved from the
+    /// node's current state
+    fn resolve_and_verify(
+        &mut self,
+        m: &ArgMatches,
+        mut mock_tx: MockTransaction,
+        genesis_info: &GenesisInfo,
+    ) -> Result<(MockTransaction, u64), String> {
+        if m.is_present("no-complete") {
+            self.check_completed(m, &mut mock_tx, genesis_info)?;
+            let cycle = self.verify_tx(m, &mut mock_tx)?;
+            Ok((mock_tx, cycle))
+        } else {
+            self.complete_mock_tx(m, mock_tx, genesis_info, true)
+        }
+    }
+
+    /// The transaction must balance by itself and every secp256k1 input must be signed
+    fn check_completed(
+        &mut self,
+        m: &ArgMatches,
+        mock_tx: &mut MockTransaction,
+        genesis_info: &GenesisInfo,
+    ) -> Result<(), String> {
+        MockTransactionHelper::new(mock_tx).check_outputs_capacity()?;
+        let mut loader = self.loader(m)?;
+        tx_fee(mock_tx, |out_point| loader.get_live_cell(out_point))
+            .map_err(|err| format!("Transaction is not complete: {}", err))?;
+        let tx = mock_tx.core_transaction();
+        let witnesses = tx.witnesses();
+        for (idx, input) in tx.inputs().into_iter().enumerate() {
+            let (output, _) = mock_tx
+                .get_input_cell(&input, |out_point| loader.get_live_cell(out_point))?
+                .ok_or_else(|| format!("input cell not found: {}", input))?;
+            if &output.lock().code_hash() != genesis_info.secp_type_hash() {
+                continue;
+            }
+            let signed = witnesses
+                .get(idx)
+                .map(|witness| witness.raw_data().iter().any(|byte| *byte != 0))
+                .unwrap_or(false);
+            if !signed {
+                return Err(format!(
+                    "Transaction is not complete: input {} (secp256k1 lock) is not signed",
+                    idx
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_tx(&mut self, m: &ArgMatches, mock_tx: &mut MockTransaction) -> Result<u64, String> {
+        let max_cycles: Option<u64> =
+            FromStrParser::<u64>::default().from_matches_opt(m, "max-cycles", false)?;
+        let group_max_cycles: Option<u64> =
+            FromStrParser::<u64>::default().from_matches_opt(m, "per-group-max-cycles", false)?;
+        let events = self.events.clone();
+        let inputs_count = mock_tx.core_transaction().inputs().len();
+        let skip_headers = m.is_present("skip-header-resolution");
+        if skip_headers {
+            self.warnings.raise(
+                "header deps are not resolved from node, verification of scripts reading headers may be inaccurate",
+            )?;
+        }
+        let only: Option<ScriptGroupKind> =
+            FromStrParser::<ScriptGroupKind>::new().from_matches_opt(m, "only-scripts", false)?;
+        if let Some(kind) = m.value_of("only-scripts") {
+            self.warnings.raise(format!(
+                "only {} script groups are verified, this is not a full acceptance check",
+                kind
+            ))?;
+        }
+        let mut loader = self.loader(m)?.with_expected_data(mock_tx);
+        loader.skip_headers = skip_headers;
+        let max_cycles = max_cycles.unwrap_or_else(u64::max_value);
+        let each_group = m.is_present("continue-on-script-error") || group_max_cycles.is_some();
+        events.phase("verifying", inputs_count, || {
+            if each_group {
+                verify_script_groups(
+                    mock_tx,
+                    max_cycles,
+                    group_max_cycles,
+                    loader,
+                    skip_headers,
+                    only,
+                )
+            } else {
+                verify_scripts(mock_tx, max_cycles, loader, skip_headers, only)
+            }
+        })
+    }
+
+    /// Verify the files on `jobs` threads, the results are in the order of `paths`
+    fn verify_files_concurrently(
+        &self,
+        m: &ArgMatches,
+        paths: &[PathBuf],
+        jobs: usize,
+    ) -> Result<Vec<(Result<u64, String>, Duration)>, String> {
+        let data_store: Option<PathBuf> =
+            DirPathParser::new(true).from_matches_opt(m, "data-store", false)?;
+        let max_cycles: u64 = FromStrParser::<u64>::default()
+            .from_matches_opt(m, "max-cycles", false)?
+            .unwrap_or_else(u64::max_value);
+        let strict_parse = m.is_present("strict-parse");
+        let (task_sender, task_receiver) = crossbeam_channel::unbounded::<(usize, PathBuf)>();
+        let (result_sender, result_receiver) = crossbeam_channel::unbounded();
+        for task in paths.iter().cloned().enumerate() {
+            task_sender.send(task).expect("task receiver alive");
+        }
+        drop(task_sender);
+        let handles = (0..jobs)
+            .map(|_| {
+                let task_receiver = task_receiver.clone();
+                let result_sender = result_sender.clone();
+                let rpc_url = self.rpc_url.clone();
+                let data_store = data_store.clone();
+                thread::spawn(move || {
+                    let mut rpc_client = HttpRpcClient::from_uri(&rpc_url);
+                    for (index, path) in task_receiver.iter() {
+                        let start = Instant::now();
+                        let result =
+                            load_mock_tx_file(&path, strict_parse).and_then(|mut mock_tx| {
+                                let mut loader =
+                                    Loader::new(&mut rpc_client).with_expected_data(&mock_tx);
+                                loader.data_store = data_store.clone();
+                                verify_scripts(&mut mock_tx, max_cycles, loader, false, None)
+                            });
+                        result_sender
+                            .send((index, (result, start.elapsed())))
+                            .expect("result receiver alive");
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(result_sender);
+        let mut results = paths.iter().map(|_| None).collect::<Vec<_>>();
+        for (index, result) in result_receiver.iter() {
+            results[index] = Some(result);
+        }
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|_| "Verify thread panicked".to_owned())?;
+        }
+        Ok(results
+            .into_iter()
+            .map(|result| result.expect("every file is verified"))
+            .collect())
+    }
+
+    /// Replace the inputs not live any more by live cells of `address` with enough capacity,
+    /// return the replaced and the added out points
+    fn rebase_inputs(
+        &mut self,
+        m: &ArgMatches,
+        mock_tx: &mut MockTransaction,
+        lock_hash: H256,
+    ) -> Result<(Vec<OutPoint>, Vec<OutPoint>), String> {
+        let inputs = mock_tx.tx.raw().inputs().into_iter().collect::<Vec<_>>();
+        let mut loader = self.loader(m)?;
+        let mut stale_indices = Vec::new();
+        let mut stale_capacities = Vec::new();
+        for (idx, input) in inputs.iter().enumerate() {
+            if loader.get_live_cell(input.previous_output())?.is_some() {
+                continue;
+            }
+            let (output, _) = mock_tx
+                .get_input_cell(input, |_| Ok(None))?
+                .ok_or_else(|| {
+                    format!(
+                        "Input #{} is not live and not in mock info, capacity to replace unknown",
+                        idx
+                    )
+                })?;
+            stale_capacities.push(Unpack::<u64>::unpack(&output.capacity()));
+            stale_indices.push(idx);
+        }
+        if stale_indices.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let stale_capacity = sum_capacities(stale_capacities, "stale inputs")?;
+        let used_out_points = inputs
+            .iter()
+            .map(|input| input.previous_output())
+            .collect::<HashSet<_>>();
+        let strategy: CollectStrategy =
+            FromStrParser::<CollectStrategy>::new().from_matches(m, "collect-strategy")?;
+        let new_out_points = CellCollector::new(self.rpc_client, strategy).collect(
+            &lock_hash,
+            stale_capacity,
+            &used_out_points,
+        )?;
+
+        let stale_out_points = stale_indices
+            .iter()
+            .map(|idx| inputs[*idx].previous_output())
+            .collect::<Vec<_>>();
+        mock_tx.tx = replace_inputs(&mock_tx.tx, &stale_indices, &new_out_points);
+        mock_tx
+            .mock_info
+            .inputs
+            .retain(|mock_input| !stale_out_points.contains(&mock_input.input.previous_output()));
+        Ok((stale_out_points, new_out_points))
+    }
+
+    /// Spend every live cell of the address into one output locked by `to_lock_arg`, the
+    /// fee at `fee_rate` is left out of the output so complete adds no change output
+    fn sweep_template(
+        &mut self,
+        genesis_info: &GenesisInfo,
+        from_address: &Address,
+        to_lock_arg: &H160,
+        fee_rate: u64,
+    ) -> Result<MockTransaction, String> {
+        let secp_type_hash = genesis_info.secp_type_hash();
+        let lock_hash: H256 = from_address
+            .lock_script(secp_type_hash.clone())
+            .calc_script_hash()
+            .unpack();
+        // Every cell is spent, the order does not matter
+        let out_points = CellCollector::new(self.rpc_client, CollectStrategy::LargestFirst)
+            .collect_all(&lock_hash)?;
+        let mut loader = Loader::new(self.rpc_client);
+        let mut mock_inputs = Vec::new();
+        for out_point in out_points {
+            let (output, data) = loader
+                .get_live_cell(out_point.clone())?
+                .ok_or_else(|| format!("Live cell not found: {}", out_point))?;
+            mock_inputs.push(MockInput {
+                input: CellInput::new(out_point, 0),
+                output,
+                data,
+            });
+        }
+        let total_capacity = sum_capacities(
+            mock_inputs
+                .iter()
+                .map(|mock_input| Unpack::<u64>::unpack(&mock_input.output.capacity())),
+            "inputs",
+        )?;
+        let output = CellOutput::new_builder()
+            .capacity(Capacity::shannons(total_capacity).pack())
+            .lock(
+                Script::new_builder()
+                    .code_hash(secp_type_hash.clone())
+                    .hash_type(ScriptHashType::Type.pack())
+                    .args(Bytes::from(to_lock_arg.as_ref()).pack())
+                    .build(),
+            )
+            .build();
+        let tx = TransactionBuilder::default()
+            .inputs(
+                mock_inputs
+                    .iter()
+                    .map(|mock_input| mock_input.input.clone()),
+            )
+            .output(output.clone())
+            .output_data(Default::default())
+            .witnesses(
+                mock_inputs
+                    .iter()
+                    .map(|_| Bytes::from(&[0u8; SECP_SIGNATURE_SIZE][..]).pack()),
+            )
+            .build();
+        // Same estimate as the change output of complete, so nothing is left for a change
+        let fee = estimate_fee(
+            &tx.as_advanced_builder()
+                .output(output.clone())
+                .output_data(Default::default())
+                .build()
+                .data(),
+            fee_rate,
+        );
+        if total_capacity < fee {
+            return Err(format!(
+                "Insufficient capacity to sweep: {} < fee {} (shannons)",
+                total_capacity, fee
+            ));
+        }
+        let output = output
+            .as_builder()
+            .capacity(Capacity::shannons(total_capacity - fee).pack())
+            .build();
+        let tx = tx
+            .as_advanced_builder()
+            .set_outputs(vec![output])
+            .build()
+            .data();
+        let mut mock_tx = MockTransaction {
+            mock_info: MockInfo {
+                inputs: mock_inputs,
+                cell_deps: Vec::new(),
+                header_deps: Vec::new(),
+            },
+            tx,
+        };
+        MockTransactionHelper::new(&mut mock_tx)
+            .fill_deps(genesis_info, |out_point| loader.get_live_cell(out_point))?;
+        Ok(mock_tx)
+    }
+
+    /// Complete, verify and send the mock transaction in the file to the connected node,
+    /// return the transaction hash and cycles
+    fn send_file(
+        &mut self,
+        m: &ArgMatches,
+        path: &Path,
+        genesis_info: &GenesisInfo,
+    ) -> Result<(H256, u64), String> {
+        let mock_tx = load_mock_tx_file(path, m.is_present("strict-parse"))?;
+        let (mock_tx, cycle) = self.complete_mock_tx(m, mock_tx, genesis_info, true)?;
+        let tx_hash = self
+            .rpc_client
+            .send_transaction(mock_tx.core_transaction().data().into())
+            .call()
+            .map_err(|err| format!("Send transaction error: {}", err))?;
+        Ok((tx_hash, cycle))
+    }
+
+    /// Parse the address argument `name`, it must be of the configured network if there is one
+    fn address_opt(&self, m: &ArgMatches, name: &str) -> Result<Option<Address>, String> {
+        let address_opt: Option<Address> = AddressParser.from_matches_opt(m, name, false)?;
+        if let (Some(network), Some(input)) = (self.network, m.value_of(name)) {
+            if !input.starts_with(network.to_prefix()) {
+                return Err(format!(
+                    "Address {} is not of the configured network {}",
+                    input, network
+                ));
+            }
+        }
+        Ok(address_opt)
+    }
+
+    /// Fee rate (shannons/KB) implied by the inputs and outputs capacity
+    fn tx_fee_rate(&mut self, m: &ArgMatches, mock_tx: &MockTransaction) -> Result<u64, String> {
+        let mut loader = self.loader(m)?.without_data();
+        let fee = tx_fee(mock_tx, |out_point| loader.get_live_cell(out_point))?;
+        fee_rate(fee, tx_size(&mock_tx.tx))
+    }
+
+    /// A cell the loaders of the command resolved which is not live any more
+    fn gone_resolved_cell(&mut self, m: &ArgMatches) -> Result<Option<OutPoint>, String> {
+        let out_points = self.resolved_cells.out_points();
+        let mut loader = self.loader(m)?.without_data();
+        for out_point in out_points {
+            if loader.get_live_cell(out_point.clone())?.is_none() {
+                return Ok(Some(out_point));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Genesis info of the system scripts, only loaded by the subcommands using it so the file
+    /// tools work without a node
+    fn load_genesis_info(&mut self, m: &ArgMatches) -> Result<GenesisInfo, String> {
+        let genesis_path: Option<PathBuf> =
+            FilePathParser::new(true).from_matches_opt(m, "genesis-file", false)?;
+        if let Some(path) = genesis_path {
+            load_genesis_file(&path)
+        } else if m.is_present("cells-file") {
+            self.genesis_info.clone().ok_or_else(|| {
+                "--cells-file verifies without node, --genesis-file is required".to_owned()
+            })
+        } else {
+            get_genesis_info(&mut self.genesis_info, self.rpc_client)
+        }
+    }
+
+    fn loader(&mut self, m: &ArgMatches) -> Result<Loader, String> {
+        let data_store: Option<PathBuf> =
+            DirPathParser::new(true).from_matches_opt(m, "data-store", false)?;
+        let offline = m.is_present("cells-file");
+        let at_block = match m.value_of("at-block") {
+            Some(input) if !offline => Some(resolve_block_number(self.rpc_client, input)?),
+            _ => None,
+        };
+        Ok(Loader {
+            rpc_client: self.rpc_client,
+            data_store,
+            skip_headers: false,
+            at_block,
+            with_data: true,
+            offline,
+            expected_data_hashes: Vec::new(),
+            resolved_cells: self.resolved_cells.clone(),
+        })
+    }
+}
+
+impl<'a> CliSubCommand for MockTxSubCommand<'a> {
+    fn process(
+        &mut self,
+        matches: &ArgMatches,
+        format: OutputFormat,
+        color: bool,
+        _debug: bool,
+    ) -> Result<String, String> {
+        self.events = EventEmitter::new(matches.is_present("json-events"));
+        self.warnings = Warnings::new(matches.is_present("strict"));
+        self.dep_changes = DepChanges::default();
+        self.resolved_cells = ResolvedCells::default();
+        let warnings = self.warnings;
+        let output_tx = |m: &ArgMatches, mock_tx: &MockTransaction| -> Result<(), String> {
+            let target = OutputTargetParser
+                .from_matches_opt(m, "output-file", false)?
+                .unwrap_or(OutputTarget::Stdout);
+            if m.is_present("preserve-format") {
+                match preserve_format(m, mock_tx) {
+                    Ok(content) => return target.write(&content),
+                    Err(err) => warnings.raise(format!(
+                        "Can not preserve the format of the tx file ({}), it is re-rendered",
+                        err
+                    ))?,
+                }
+            }
+            let output_color = target.is_stdout() && color;
+            let format_capacity =
+                m.value_of("capacity-unit") == Some("ckb") && !m.is_present("shannons-only");
+            let output_content = if format_capacity || m.is_present("annotate-inputs") {
+                let mut value = serde_json::to_value(ReprMockTransaction::from(mock_tx.clone()))
+                    .map_err(|err| err.to_string())?;
+                if m.is_present("annotate-inputs") {
+                    annotate_inputs(&mut value, mock_tx);
+                }
+                if format_capacity {
+                    capacity::format_capacities(&mut value)?;
+                }
+                value.render(OutputFormat::Json, output_color)
+            } else {
+                ReprMockTransaction::from(mock_tx.clone()).render(OutputFormat::Json, output_color)
+            };
+            target.write(&output_content)
+        };
+
+        match matches.subcommand() {
+            ("template", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let lock_arg_opt: Option<H160> =
+                    FixedHashParser::<H160>::default().from_matches_opt(m, "lock-arg", false)?;
+                let from_address_opt = self.address_opt(m, "from-address")?;
+                let capacity_opt: Option<u64> =
+                    CapacityParser.from_matches_opt(m, "capacity", false)?;
+                if m.is_present("sweep") {
+                    let from_address = from_address_opt.expect("from-address required by sweep");
+                    let to_lock_arg: H160 =
+                        FixedHashParser::<H160>::default().from_matches(m, "to-lock-arg")?;
+                    let fee_rate: u64 = FromStrParser::<u64>::default()
+                        .from_matches_opt(m, "fee-rate", false)?
+                        .or(self.fee_rate)
+                        .unwrap_or(0);
+                    let mock_tx =
+                        self.sweep_template(&genesis_info, &from_address, &to_lock_arg, fee_rate)?;
+                    output_tx(m, &mock_tx)?;
+                    return Ok(String::new());
+                }
+                let lock_arg = lock_arg_opt
+                    .or_else(|| {
+                        from_address_opt
+                            .as_ref()
+                            .map(|address| address.hash().clone())
+                    })
+                    .unwrap_or_else(H160::default);
+                let lock_code_hash_opt: Option<H256> = FixedHashParser::<H256>::default()
+                    .from_matches_opt(m, "lock-code-hash", false)?;
+                let lock_args_opt: Option<Vec<u8>> =
+                    HexParser.from_matches_opt(m, "lock-args", false)?;
+                let lock_hash_type = match m.value_of("lock-hash-type") {
+                    Some("data") => ScriptHashType::Data,
+                    _ => ScriptHashType::Type,
+                };
+                let secp_type_hash = genesis_info.secp_type_hash();
+                let sample_script = || match lock_code_hash_opt.as_ref() {
+                    Some(lock_code_hash) => Script::new_builder()
+                        .code_hash(lock_code_hash.pack())
+                        .hash_type(lock_hash_type.pack())
+                        .args(Bytes::from(lock_args_opt.clone().unwrap_or_default()).pack())
+                        .build(),
+                    None => Script::new_builder()
+                        .code_hash(secp_type_hash.clone())
+                        .hash_type(ScriptHashType::Type.pack())
+                        .args(Bytes::from(lock_arg.as_ref()).pack())
+                        .build(),
+                };
+                let code_cell_dep = |index: u32| MockCellDep {
+                    cell_dep: CellDep::new_builder()
+                        .out_point(OutPoint::new(h256!("0xff01").pack(), index))
+                        .build(),
+                    output: CellOutput::new_builder()
+                        .capacity(capacity_bytes!(600).pack())
+                        .lock(sample_script())
+                        .build(),
+                    data: Bytes::from("1234"),
+                };
+                let cell_dep_types = m
+                    .values_of("cell-dep-type")
+                    .map(|values| values.collect::<Vec<_>>())
+                    .unwrap_or_default();
+                let mut mock_cell_deps = Vec::new();
+                let mut cell_deps = Vec::new();
+                for (index, dep_type) in cell_dep_types.into_iter().enumerate() {
+                    let code_dep = code_cell_dep(index as u32);
+                    if dep_type == "dep_group" {
+                        let group_data = OutPointVec::new_builder()
+                            .push(code_dep.cell_dep.out_point())
+                            .build();
+                        let group_dep = MockCellDep {
+                            cell_dep: CellDep::new_builder()
+                                .out_point(OutPoint::new(h256!("0xff03").pack(), index as u32))
+                                .dep_type(DepType::DepGroup.pack())
+                                .build(),
+                            output: CellOutput::new_builder()
+                                .capacity(capacity_bytes!(600).pack())
+                                .lock(sample_script())
+                                .build(),
+                            data: group_data.as_bytes(),
+                        };
+                        cell_deps.push(group_dep.cell_dep.clone());
+                        mock_cell_deps.push(group_dep);
+                    } else {
+                        cell_deps.push(code_dep.cell_dep.clone());
+                    }
+                    mock_cell_deps.push(code_dep);
+                }
+                if mock_cell_deps.is_empty() {
+                    mock_cell_deps.push(code_cell_dep(0));
+                }
+                let mock_inputs = if let Some(from_address) = from_address_opt {
+                    let capacity = capacity_opt.ok_or_else(|| {
+                        "--from-address requires --capacity or --sweep".to_owned()
+                    })?;
+                    let lock_hash: H256 = from_address
+                        .lock_script(secp_type_hash.clone())
+                        .calc_script_hash()
+                        .unpack();
+                    let strategy: CollectStrategy = FromStrParser::<CollectStrategy>::new()
+                        .from_matches(m, "collect-strategy")?;
+                    let out_points = CellCollector::new(self.rpc_client, strategy).collect(
+                        &lock_hash,
+                        capacity,
+                        &HashSet::new(),
+                    )?;
+                    let mut loader = Loader::new(self.rpc_client);
+                    let mut mock_inputs = Vec::new();
+                    for out_point in out_points {
+                        let (output, data) = loader
+                            .get_live_cell(out_point.clone())?
+                            .ok_or_else(|| format!("Live cell not found: {}", out_point))?;
+                        mock_inputs.push(MockInput {
+                            input: CellInput::new(out_point, 0),
+                            output,
+                            data,
+                        });
+                    }
+                    mock_inputs
+                } else {
+                    vec![MockInput {
+                        input: CellInput::new(OutPoint::new(h256!("0xff02").pack(), 0), 0),
+                        output: CellOutput::new_builder()
+                            .capacity(capacity_bytes!(300).pack())
+                            .lock(sample_script())
+                            .build(),
+                        data: Bytes::from("abcd"),
+                    }]
+                };
+                let inputs = mock_inputs
+                    .iter()
+                    .map(|mock_input| mock_input.input.clone())
+                    .collect::<Vec<_>>();
+                let output_capacity = capacity_opt
+                    .map(Capacity::shannons)
+                    .unwrap_or_else(|| capacity_bytes!(120));
+                let output = CellOutput::new_builder()
+                    .capacity(output_capacity.pack())
+                    .lock(sample_script())
+                    .type_(Some(sample_script()).pack())
+                    .build();
+
+                let mock_info = MockInfo {
+                    inputs: mock_inputs,
+                    cell_deps: mock_cell_deps,
+                    header_deps: vec![HeaderBuilder::default().build()],
+                };
+                let tx = TransactionBuilder::default()
+                    .cell_deps(cell_deps)
+                    .output(output)
+                    .output_data(Default::default())
+                    // Placeholders of the secp256k1 signatures, replaced when signing
+                    .witnesses(
+                        inputs
+                            .iter()
+                            .map(|_| Bytes::from(&[0u8; SECP_SIGNATURE_SIZE][..]).pack()),
+                    )
+                    .inputs(inputs)
+                    .build()
+                    .data();
+                let mut mock_tx = MockTransaction { mock_info, tx };
+                // The code cell of a custom lock is unknown, add it to cell deps by hand
+                if lock_code_hash_opt.is_none() {
+                    let mut helper = MockTransactionHelper::new(&mut mock_tx);
+                    helper.fill_deps(&genesis_info, |_| unreachable!())?;
+                }
+                attach_stdin_output_data(m, &mut mock_tx)?;
+                output_tx(m, &mock_tx)?;
+
+                Ok(String::new())
+            }
+            ("complete", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let (mock_tx, _cycle) = self.complete_tx(m, &genesis_info, false)?;
+                if m.is_present("base64-out") {
+                    let target = OutputTargetParser
+                        .from_matches_opt(m, "output-file", false)?
+                        .unwrap_or(OutputTarget::Stdout);
+                    target.write(&base64::encode_config(
+                        mock_tx.tx.as_slice(),
+                        base64::URL_SAFE_NO_PAD,
+                    ))?;
+                } else if m.is_present("emit-rpc-request") {
+                    let target = OutputTargetParser
+                        .from_matches_opt(m, "output-file", false)?
+                        .unwrap_or(OutputTarget::Stdout);
+                    let rpc_tx: RpcTransaction = mock_tx.core_transaction().data().into();
+                    let request = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": "send_transaction",
+                        "params": [rpc_tx],
+                    });
+                    target
+                        .write(&request.render(OutputFormat::Json, target.is_stdout() && color))?;
+                } else {
+                    output_tx(m, &mock_tx)?;
+                }
+                let fee_rate = self.tx_fee_rate(m, &mock_tx)?;
+                let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
+                let output_type_kinds = mock_tx
+                    .core_transaction()
+                    .outputs()
+                    .into_iter()
+                    .map(|output| inspect::type_kind(&output, genesis_info.dao_type_hash()))
+                    .collect::<Vec<_>>();
+                let mut resp = serde_json::json!({
+                    "tx-hash": tx_hash,
+                    "fee_rate": fee_rate,
+                    "output_type_kinds": output_type_kinds,
+                });
+                self.dep_changes.report(&mut resp);
+                Ok(resp.render(format, color))
+            }
+            ("verify", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let current_block: Option<u64> =
+                    FromStrParser::<u64>::default().from_matches_opt(m, "current-block", false)?;
+                let current_epoch: Option<EpochNumberWithFraction> =
+                    EpochParser.from_matches_opt(m, "current-epoch", false)?;
+                let allowed_code_hashes: Vec<H256> =
+                    FixedHashParser::<H256>::default().from_matches_vec(m, "allowed-code-hash")?;
+                let loaded_tx = load_mock_tx(m)?;
+                let mut resolution_retried = false;
+                let (mock_tx, cycle) =
+                    match self.resolve_and_verify(m, loaded_tx.clone(), &genesis_info) {
+                        // Only a cell resolved before and gone now (the node may be reorganizing)
+                        // is worth a retry, any other failure would just repeat
+                        Err(err) if m.is_present("snapshot-mismatch-ok") => {
+                            if self.gone_resolved_cell(m)?.is_none() {
+                                return Err(err);
+                            }
+                            resolution_retried = true;
+                            self.resolve_and_verify(m, loaded_tx, &genesis_info)?
+                        }
+                        result => result?,
+                    };
+                if !allowed_code_hashes.is_empty() {
+                    let mut loader = self.loader(m)?;
+                    check_allowed_code_hashes(
+                        &mock_tx,
+                        &allowed_code_hashes.into_iter().collect(),
+                        |out_point| loader.get_live_cell(out_point),
+                    )?;
+                }
+                // Informational, a script may load a cell dep as data
+                let unused = {
+                    let resolved_cells = self.resolved_cells.clone();
+                    let mut loader = self.loader(m)?;
+                    // Resolved by the verification, the node is only asked for the cells missed
+                    unused_cell_deps(&mock_tx, |out_point| match resolved_cells.get(&out_point) {
+                        Some(cell) => Ok(Some(cell)),
+                        None => loader.get_live_cell(out_point),
+                    })?
+                };
+                self.dep_changes.unused = unused.iter().map(cell_dep_desc).collect();
+                let fee_rate = self.tx_fee_rate(m, &mock_tx)?;
+                let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
+                let mut immature_inputs =
+                    check_inputs_since(&mock_tx, current_block, current_epoch);
+                // The creation blocks of the inputs are only known by the node
+                if m.is_present("check-relative-since") {
+                    immature_inputs.extend(check_inputs_relative_since(self.rpc_client, &mock_tx)?);
+                }
+                if !immature_inputs.is_empty() {
+                    let resp = serde_json::json!({
+                        "tx-hash": tx_hash,
+                        "cycle": cycle,
+                        "fee_rate": fee_rate,
+                        "immature-inputs": immature_inputs,
+                    });
+                    return Err(resp.render(format, color));
+                }
+                let dao_report = if m.is_present("check-dao") {
+                    let mut loader = self.loader(m)?;
+                    let (report, ok) = dao::check_dao_withdrawals(
+                        &mut loader,
+                        &mock_tx,
+                        genesis_info.dao_type_hash(),
+                    )?;
+                    if !ok {
+                        let resp = serde_json::json!({
+                            "tx-hash": tx_hash,
+                            "cycle": cycle,
+                            "dao": report,
+                        });
+                        return Err(resp.render(format, color));
+                    }
+                    Some(report)
+                } else {
+                    None
+                };
+                let mut resp = serde_json::json!({
+                    "tx-hash": tx_hash,
+                    "cycle": cycle,
+                    "fee_rate": fee_rate,
+                });
+                if let Some(report) = dao_report {
+                    resp["dao"] = report;
+                }
+                self.dep_changes.report(&mut resp);
+                if resolution_retried {
+                    resp["resolution-retried"] = serde_json::json!(true);
+                }
+                if m.is_present("show-timing") {
+                    resp["timing"] = self.events.timings();
+                }
+                Ok(resp.render(format, color))
+            }
+            ("bundle", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let (mock_tx, cycle) = self.complete_tx(m, &genesis_info, true)?;
+                let loader = self.loader(m)?.with_expected_data(&mock_tx);
+                let bundled_tx = bundle::bundle_tx(&mock_tx, loader)?;
+                // Make sure nothing is missing before saving it
+                let mut check_tx = bundled_tx.clone();
+                MockTransactionHelper::new(&mut check_tx)
+                    .verify_opt(u64::max_value(), OfflineLoader, false)
+                    .map_err(|err| format!("Bundle is not self-contained: {}", err))?;
+                output_tx(m, &bundled_tx)?;
+                let tx_hash: H256 = bundled_tx.core_transaction().hash().unpack();
+                let mut resp = serde_json::json!({
+                    "tx-hash": tx_hash,
+                    "cycle": cycle,
+                });
+                self.dep_changes.report(&mut resp);
+                Ok(resp.render(format, color))
+            }
+            ("rebase", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let address = self
+                    .address_opt(m, "address")?
+                    .expect("address is required");
+                let lock_hash: H256 = address
+                    .lock_script(genesis_info.secp_type_hash().clone())
+                    .calc_script_hash()
+                    .unpack();
+                let mut mock_tx = load_mock_tx(m)?;
+                let (replaced, added) = self.rebase_inputs(m, &mut mock_tx, lock_hash)?;
+                let (mock_tx, _cycle) = self.complete_mock_tx(m, mock_tx, &genesis_info, false)?;
+                output_tx(m, &mock_tx)?;
+                let out_point_str = |out_point: &OutPoint| {
+                    let index: u32 = out_point.index().unpack();
+                    format!("{:#x}-{}", out_point.tx_hash(), index)
+                };
+                let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
+                let mut resp = serde_json::json!({
+                    "tx-hash": tx_hash,
+                    "replaced": replaced.iter().map(out_point_str).collect::<Vec<_>>(),
+                    "added": added.iter().map(out_point_str).collect::<Vec<_>>(),
+                });
+                self.dep_changes.report(&mut resp);
+                Ok(resp.render(format, color))
+            }
+            ("verify-all", Some(m)) => {
+                let dir: PathBuf = DirPathParser::new(true).from_matches(m, "dir")?;
+                let count_only = m.is_present("count-only");
+                let mut paths = fs::read_dir(&dir)
+                    .map_err(|err| err.to_string())?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_file() && is_mock_tx_path(path))
+                    .collect::<Vec<_>>();
+                paths.sort();
+                let metrics_out: Option<PathBuf> =
+                    FilePathParser::new(false).from_matches_opt(m, "metrics-out", false)?;
+                let mut metrics = BatchMetrics::new("verify-all");
+                let batch_start = Instant::now();
+                let jobs = FromStrParser::<usize>::default()
+                    .from_matches(m, "jobs")?
+                    .min(MAX_JOBS)
+                    .min(paths.len());
+                let results = if jobs > 1 {
+                    self.verify_files_concurrently(m, &paths, jobs)?
+                } else {
+                    paths
+                        .iter()
+                        .map(|path| {
+                            let start = Instant::now();
+                            let result = load_mock_tx_file(path, m.is_present("strict-parse"))
+                                .and_then(|mut mock_tx| self.verify_tx(m, &mut mock_tx));
+                            (result, start.elapsed())
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let mut passed = 0;
+                let mut lines = Vec::new();
+                for (path, (result, duration)) in paths.iter().zip(results) {
+                    metrics.record(result.as_ref().ok().cloned(), duration);
+                    match result {
+                        Ok(cycle) => {
+                            passed += 1;
+                            lines.push(format!("PASS {} (cycles: {})", path.display(), cycle));
+                        }
+                        Err(err) => lines.push(format!("FAIL {}: {}", path.display(), err)),
+                    }
+                }
+                if !count_only {
+                    for line in &lines {
+                        println!("{}", line);
+                    }
+                }
+                if let Some(path) = metrics_out {
+                    metrics.finish(batch_start.elapsed());
+                    metrics.write_to(&path)?;
+                }
+                let summary = format!("{}/{}", passed, paths.len());
+                if passed == paths.len() {
+                    Ok(summary)
+                } else {
+                    Err(summary)
+                }
+            }
+            ("send-all", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let dir: PathBuf = DirPathParser::new(true).from_matches(m, "dir")?;
+                let keep_going = m.is_present("keep-going");
+                let mut paths = fs::read_dir(&dir)
+                    .map_err(|err| err.to_string())?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_file() && is_mock_tx_path(path))
+                    .collect::<Vec<_>>();
+                paths.sort();
+                check_send_sync_state(self.rpc_client, m.is_present("force"))?;
+                self.sync_checked = true;
+                let metrics_out: Option<PathBuf> =
+                    FilePathParser::new(false).from_matches_opt(m, "metrics-out", false)?;
+                let mut metrics = BatchMetrics::new("send-all");
+                let batch_start = Instant::now();
+                let mut success = 0;
+                let mut results = Vec::new();
+                for path in &paths {
+                    let start = Instant::now();
+                    let result = self.send_file(m, path, &genesis_info);
+                    metrics.record(
+                        result.as_ref().ok().map(|(_, cycle)| *cycle),
+                        start.elapsed(),
+                    );
+                    match result {
+                        Ok((tx_hash, _cycle)) => {
+                            success += 1;
+                            results.push(serde_json::json!({
+                                "file": path.display().to_string(),
+                                "tx-hash": tx_hash,
+                            }));
+                        }
+                        Err(err) => {
+                            results.push(serde_json::json!({
+                                "file": path.display().to_string(),
+                                "error": err,
+                            }));
+                            if !keep_going {
+                                break;
+                            }
+                        }
+                    }
+                }
+                if let Some(path) = metrics_out {
+                    metrics.finish(batch_start.elapsed());
+                    metrics.write_to(&path)?;
+                }
+                let resp = serde_json::json!({
+                    "success": success,
+                    "failed": results.len() - success,
+                    "skipped": paths.len() - results.len(),
+                    "results": results,
+                });
+                if success == paths.len() {
+                    Ok(resp.render(format, color))
+                } else {
+                    Err(resp.render(format, color))
+                }
+            }
+            ("canonicalize", Some(m)) => {
+                let mut mock_tx = load_mock_tx(m)?;
+                mock_tx.canonicalize();
+                attach_stdin_output_data(m, &mut mock_tx)?;
+                output_tx(m, &mock_tx)?;
+                Ok(String::new())
+            }
+            ("inspect", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let data_limit = if !m.is_present("show-data") {
+                    None
+                } else if m.is_present("full-data") {
+                    Some(usize::max_value())
+                } else {
+                    Some(FromStrParser::<usize>::default().from_matches(m, "data-limit")?)
+                };
+                let mock_tx = load_mock_tx(m)?;
+                let mut loader = self.loader(m)?;
+                let summary = inspect::inspect_tx(
+                    &mock_tx,
+                    m.is_present("expand-groups"),
+                    data_limit,
+                    genesis_info.dao_type_hash(),
+                    |out_point| loader.get_live_cell(out_point),
+                )?;
+                Ok(summary.render(format, color))
+            }
+            ("groups", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let mock_tx = load_mock_tx(m)?;
+                let controlled_lock_args: HashSet<H160> =
+                    self.key_store.get_accounts().keys().cloned().collect();
+                let mut loader = self.loader(m)?;
+                let groups = inspect::lock_groups(
+                    &mock_tx,
+                    genesis_info.secp_type_hash(),
+                    &controlled_lock_args,
+                    |out_point| loader.get_live_cell(out_point),
+                )?;
+                Ok(groups.render(format, color))
+            }
+            ("set-witness", Some(m)) => {
+                let index: usize = FromStrParser::<usize>::default().from_matches(m, "index")?;
+                let witness: Vec<u8> = HexParser.from_matches(m, "witness")?;
+                let mut mock_tx = load_mock_tx(m)?;
+                mock_tx.tx = set_witness(&mock_tx.tx, index, Bytes::from(witness))?;
+                attach_stdin_output_data(m, &mut mock_tx)?;
+                output_tx(m, &mock_tx)?;
+                Ok(String::new())
+            }
+            ("hash", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                Ok(format!("{:#x}", mock_tx.core_transaction().hash()))
+            }
+            ("witness-hash", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                Ok(format!("{:#x}", mock_tx.core_transaction().witness_hash()))
+            }
+            ("header-deps", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                let mut loader = Loader::new(self.rpc_client);
+                let mut missing = 0;
+                let mut headers = Vec::new();
+                for block_hash in mock_tx.tx.raw().header_deps().into_iter() {
+                    let block_hash: H256 = block_hash.unpack();
+                    let in_file = mock_tx
+                        .mock_info
+                        .header_deps
+                        .iter()
+                        .any(|header| Unpack::<H256>::unpack(&header.hash()) == block_hash);
+                    let header_opt =
+                        mock_tx.get_header(&block_hash, |hash| loader.get_header(hash))?;
+                    headers.push(match header_opt {
+                        Some(header) => {
+                            let epoch = header.epoch();
+                            serde_json::json!({
+                                "hash": block_hash,
+                                "number": header.number(),
+                                "epoch": epoch.number(),
+                                "epoch-index": epoch.index(),
+                                "epoch-length": epoch.length(),
+                                "source": if in_file { "file" } else { "node" },
+                            })
+                        }
+                        None => {
+                            missing += 1;
+                            serde_json::json!({
+                                "hash": block_hash,
+                                "missing": true,
+                            })
+                        }
+                    });
+                }
+                let resp = serde_json::json!({
+                    "header-deps": headers,
+                    "missing": missing,
+                });
+                if missing > 0 {
+                    return Err(resp.render(format, color));
+                }
+                Ok(resp.render(format, color))
+            }
+            ("compare-chain", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                let tx = mock_tx.core_transaction();
+                let tx_hash: H256 = tx.hash().unpack();
+                let tx_with_status = self
+                    .rpc_client
+                    .get_transaction(tx_hash.clone())
+                    .call()
+                    .map_err(|err| format!("Send get_transaction error: {}", err))?
+                    .0;
+                let resp = match tx_with_status {
+                    Some(tx_with_status) => {
+                        let node_tx: Transaction = tx_with_status.transaction.inner.into();
+                        let status = serde_json::to_value(&tx_with_status.tx_status.status)
+                            .map_err(|err| err.to_string())?;
+                        serde_json::json!({
+                            "tx-hash": tx_hash,
+                            "exists": true,
+                            "status": status,
+                            "block-hash": tx_with_status.tx_status.block_hash,
+                            "bytes-match": node_tx.as_slice() == tx.data().as_slice(),
+                            "witness-hash": format!("{:#x}", tx.witness_hash()),
+                            "node-witness-hash": format!("{:#x}", node_tx.calc_witness_hash()),
+                        })
+                    }
+                    None => serde_json::json!({
+                        "tx-hash": tx_hash,
+                        "exists": false,
+                    }),
+                };
+                Ok(resp.render(format, color))
+            }
+            ("script-hash", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let address = match AddressParser.from_matches_opt(m, "address", false)? {
+                    Some(address) => address,
+                    None => {
+                        let lock_arg: H160 =
+                            FixedHashParser::<H160>::default().from_matches(m, "lock-arg")?;
+                        Address::from_lock_arg(lock_arg.as_bytes())?
+                    }
+                };
+                let lock_hash = address
+                    .lock_script(genesis_info.secp_type_hash().clone())
+                    .calc_script_hash();
+                Ok(format!("{:#x}", lock_hash))
+            }
+            ("since", Some(m)) => {
+                let (metric, value) =
+                    match FromStrParser::<u64>::default().from_matches_opt(m, "block", false)? {
+                        Some(number) => (SinceMetric::BlockNumber, number),
+                        None => (
+                            SinceMetric::EpochNumber,
+                            FromStrParser::<u64>::default().from_matches(m, "epoch")?,
+                        ),
+                    };
+                let since = Since::new(metric, value, m.is_present("relative"))?;
+                Ok(format!("{:#x}", since.0))
+            }
+            ("graph", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                Ok(graph::to_dot(&mock_tx))
+            }
+            ("molecule", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                Ok(molecule::layout(&mock_tx.tx).render(format, color))
+            }
+            ("export-witnesses", Some(m)) => {
+                let mock_tx = load_mock_tx(m)?;
+                let witnesses = mock_tx
+                    .tx
+                    .witnesses()
+                    .into_iter()
+                    .map(|witness| JsonBytes::from_bytes(witness.raw_data()))
+                    .collect::<Vec<_>>();
+                serde_json::to_string_pretty(&witnesses).map_err(|err| err.to_string())
+            }
+            ("import-witnesses", Some(m)) => {
+                let witnesses_path: PathBuf =
+                    FilePathParser::new(true).from_matches(m, "witnesses")?;
+                let content = fs::read_to_string(&witnesses_path).map_err(|err| err.to_string())?;
+                let witnesses: Vec<JsonBytes> = serde_json::from_str(&content)
+                    .map_err(|err| format!("Parse witnesses file error: {}", err))?;
+                let mut mock_tx = load_mock_tx(m)?;
+                mock_tx.tx = mock_tx
+                    .tx
+                    .as_advanced_builder()
+                    .set_witnesses(
+                        witnesses
+                            .into_iter()
+                            .map(|witness| witness.into_bytes().pack())
+                            .collect(),
+                    )
+                    .build()
+                    .data();
+                attach_stdin_output_data(m, &mut mock_tx)?;
+                output_tx(m, &mock_tx)?;
+                Ok(String::new())
+            }
+            ("replace-dep", Some(m)) => {
+                let old_out_point: OutPoint = OutPointParser.from_matches(m, "old")?;
+                let new_out_point: OutPoint = OutPointParser.from_matches(m, "new")?;
+                let mut mock_tx = load_mock_tx(m)?;
+                let replaced = replace_cell_dep(&mut mock_tx, &old_out_point, &new_out_point);
+                output_tx(m, &mock_tx)?;
+                let resp = serde_json::json!({
+                    "replaced": replaced,
+                });
+                Ok(resp.render(format, color))
+            }
+            ("replace-lock", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let lock_arg: H160 =
+                    FixedHashParser::<H160>::default().from_matches(m, "lock-arg")?;
+                let mut mock_tx = load_mock_tx(m)?;
+                let indices: Vec<usize> = if m.is_present("all-outputs") {
+                    (0..mock_tx.tx.raw().outputs().len()).collect()
+                } else {
+                    FromStrParser::<usize>::default().from_matches_vec(m, "index")?
+                };
+                let lock = Script::new_builder()
+                    .code_hash(genesis_info.secp_type_hash().clone())
+                    .hash_type(ScriptHashType::Type.pack())
+                    .args(Bytes::from(lock_arg.as_ref()).pack())
+                    .build();
+                mock_tx.tx = replace_output_locks(&mock_tx.tx, &indices, &lock)?;
+                output_tx(m, &mock_tx)?;
+                let resp = serde_json::json!({
+                    "replaced-outputs": indices,
+                });
+                Ok(resp.render(format, color))
+            }
+            ("reorder-outputs", Some(m)) => {
+                let order: Vec<usize> =
+                    FromStrParser::<usize>::default().from_matches_vec(m, "order")?;
+                let mut mock_tx = load_mock_tx(m)?;
+                mock_tx.tx = reorder_outputs(&mock_tx.tx, &order)?;
+                output_tx(m, &mock_tx)?;
+                Ok(String::new())
+            }
+            ("signing-request", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let mock_tx = load_mock_tx(m)?;
+                let mut loader = self.loader(m)?;
+                let request = signing::signing_request(&mock_tx, &genesis_info, |out_point| {
+                    loader.get_live_cell(out_point)
+                })?;
+                Ok(request.render(format, color))
+            }
+            ("sign-hashes", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let mock_tx = load_mock_tx(m)?;
+                let mut loader = self.loader(m)?;
+                let hashes = signing::sign_hashes(
+                    &mock_tx,
+                    &genesis_info,
+                    m.is_present("dump-preimage"),
+                    |out_point| loader.get_live_cell(out_point),
+                )?;
+                Ok(hashes.render(format, color))
+            }
+            ("import-signatures", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let signatures_path: PathBuf =
+                    FilePathParser::new(true).from_matches(m, "signatures")?;
+                let content =
+                    fs::read_to_string(&signatures_path).map_err(|err| err.to_string())?;
+                let signatures = signing::parse_signatures(&content)?;
+                let mut mock_tx = load_mock_tx(m)?;
+                let mut loader = self.loader(m)?;
+                let signed = signing::import_signatures(
+                    &mut mock_tx,
+                    &genesis_info,
+                    &signatures,
+                    |out_point| loader.get_live_cell(out_point),
+                )?;
+                output_tx(m, &mock_tx)?;
+                let resp = serde_json::json!({
+                    "signed-inputs": signed,
+                });
+                Ok(resp.render(format, color))
+            }
+            ("fuzz", Some(m)) => {
+                let iterations: usize =
+                    FromStrParser::<usize>::default().from_matches(m, "iterations")?;
+                let max_cycles: u64 =
+                    FromStrParser::<u64>::default().from_matches(m, "max-cycles")?;
+                let seed: u64 = FromStrParser::<u64>::default()
+                    .from_matches_opt(m, "seed", false)?
+                    .unwrap_or_else(rand::random);
+                let mock_tx = load_mock_tx(m)?;
+                let loader = self.loader(m)?;
+                // Mutations are verified offline, the cell data to mutate must be in mock info
+                let base_tx = bundle::bundle_tx(&mock_tx, loader)?;
+                if let fuzz::Outcome::Crash(err) = fuzz::verify(&base_tx, max_cycles) {
+                    return Err(format!("The base transaction crashes already: {}", err));
+                }
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut passed = 0;
+                let mut script_failures = 0;
+                let mut crashes = Vec::new();
+                for iteration in 0..iterations {
+                    let (mutated_tx, mutation) = fuzz::mutate(&base_tx, &mut rng)
+                        .ok_or_else(|| "Nothing to mutate: no witnesses or cell data".to_owned())?;
+                    match fuzz::verify(&mutated_tx, max_cycles) {
+                        fuzz::Outcome::Passed => passed += 1,
+                        fuzz::Outcome::ScriptFailure(_) => script_failures += 1,
+                        fuzz::Outcome::Crash(err) => crashes.push(serde_json::json!({
+                            "iteration": iteration,
+                            "mutation": mutation,
+                            "error": err,
+                        })),
+                    }
+                }
+                let resp = serde_json::json!({
+                    "seed": seed,
+                    "iterations": iterations,
+                    "passed": passed,
+                    "script-failures": script_failures,
+                    "crashes": crashes,
+                });
+                if crashes.is_empty() {
+                    Ok(resp.render(format, color))
+                } else {
+                    Err(resp.render(format, color))
+                }
+            }
+            ("explain-error", Some(m)) => {
+                let code: i8 = FromStrParser::<i8>::default().from_matches(m, "code")?;
+                Ok(explain::explain_code(code))
+            }
+            ("test-pool", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let (mock_tx, _cycle) = self.complete_tx(m, &genesis_info, false)?;
+                let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
+                match self
+                    .rpc_client
+                    .test_tx_pool_accept(mock_tx.core_transaction().data().into())
+                    .call()
+                {
+                    Ok(result) => {
+                        let resp = serde_json::json!({
+                            "tx-hash": tx_hash,
+                            "accepted": true,
+                            "cycles": result.cycles.value(),
+                            "fee": result.fee.value(),
+                        });
+                        Ok(resp.render(format, color))
+                    }
+                    Err(err) => {
+                        let err = err.to_string();
+                        if err.contains("Method not found") || err.contains("-32601") {
+                            return Err(format!(
+                                "Tx-pool acceptance check is not supported by node {} (no test_tx_pool_accept rpc)",
+                                self.rpc_url
+                            ));
+                        }
+                        let resp = serde_json::json!({
+                            "tx-hash": tx_hash,
+                            "accepted": false,
+                            "reason": err,
+                        });
+                        Err(resp.render(format, color))
+                    }
+                }
+            }
+            ("send", Some(m)) => {
+                let genesis_info = self.load_genesis_info(m)?;
+                let rpc_urls: Vec<Url> = UrlParser.from_matches_vec(m, "rpc-url")?;
+                let force = m.is_present("force");
+                if rpc_urls.is_empty() {
+                    // Checked before completing, the completion does not check it again
+                    check_send_sync_state(self.rpc_client, force)?;
+                    self.sync_checked = true;
+                }
+                let (mut mock_tx, _cycle) = self.complete_tx(m, &genesis_info, true)?;
+                if let Some(hook) = m.value_of("pre-send-hook") {
+                    mock_tx.tx = run_pre_send_hook(hook, &mock_tx)?;
+                    self.verify_tx(m, &mut mock_tx)
+                        .map_err(|err| format!("Verify hooked transaction failed: {}", err))?;
+                }
+                let max_fee_rate: Option<u64> =
+                    FromStrParser::<u64>::default().from_matches_opt(m, "max-fee-rate", false)?;
+                let broadcast_log: Option<PathBuf> =
+                    FilePathParser::new(false).from_matches_opt(m, "broadcast-log", false)?;
+                let tx_size = tx_size(&mock_tx.tx);
+                let interactive = m.is_present("interactive");
+                let fee = if max_fee_rate.is_some() || broadcast_log.is_some() || interactive {
+                    let mut loader = self.loader(m)?;
+                    tx_fee(&mock_tx, |out_point| loader.get_live_cell(out_point))?
+                } else {
+                    0
+                };
+                if let Some(max_fee_rate) = max_fee_rate {
+                    let fee_rate = fee_rate(fee, tx_size)?;
+                    if fee_rate > max_fee_rate {
+                        return Err(format!(
+                            "Fee rate too high: {} shannons/KB (fee: {}, size: {}) > {} (--max-fee-rate)",
+                            fee_rate, fee, tx_size, max_fee_rate
+                        ));
+                    }
+                }
+                if interactive {
+                    let mut loader = self.loader(m)?;
+                    let mut summary = inspect::inspect_tx(
+                        &mock_tx,
+                        false,
+                        None,
+                        genesis_info.dao_type_hash(),
+                        |out_point| loader.get_live_cell(out_point),
+                    )?;
+                    summary["fee"] = serde_json::json!(fee);
+                    eprintln!("{}", summary.render(format, color));
+                    confirm_send()?;
+                }
+                let log_broadcast = |node: &str, tx_hash: &H256| -> Result<(), String> {
+                    match broadcast_log.as_ref() {
+                        Some(path) => append_broadcast_log(path, node, tx_hash, fee, tx_size),
+                        None => Ok(()),
+                    }
+                };
+                let verify_after_send = m.is_present("verify-after-send");
+                let events = self.events.clone();
+                if rpc_urls.is_empty() {
+                    let resp = events.phase("sending", 1, || {
+                        self.rpc_client
+                            .send_transaction(mock_tx.core_transaction().data().into())
+                            .call()
+                            .map_err(|err| format!("Send transaction error: {}", err))
+                    })?;
+                    log_broadcast(&self.rpc_url, &resp)?;
+                    if verify_after_send {
+                        check_sent_transaction(self.rpc_client, &resp, &mock_tx.tx)?;
+                    }
+                    return Ok(resp.render(format, color));
+                }
+
+                // Submit to every node, one failure should not stop the others
+                let mut success = 0;
+                let results = events.phase("sending", rpc_urls.len(), || {
+                    Ok(rpc_urls
+                        .iter()
+                        .map(|url| {
+                            let mut rpc_client = HttpRpcClient::from_uri(url.as_str());
+                            if let Err(err) = check_send_sync_state(&mut rpc_client, force) {
+                                return serde_json::json!({
+                                    "url": url.as_str(),
+                                    "error": err,
+                                });
+                            }
+                            match rpc_client
+                                .send_transaction(mock_tx.core_transaction().data().into())
+                                .call()
+                            {
+                                Ok(tx_hash) => {
+                                    let check_result = log_broadcast(url.as_str(), &tx_hash)
+                                        .and_then(|_| {
+                                            if verify_after_send {
+                                                check_sent_transaction(
+                                                    &mut rpc_client,
+                                                    &tx_hash,
+                                                    &mock_tx.tx,
+                                                )
+                                            } else {
+                                                Ok(())
+                                            }
+                                        });
+                                    match check_result {
+                                        Ok(_) => {
+                                            success += 1;
+                                            serde_json::json!({
+                                                "url": url.as_str(),
+                                                "tx-hash": tx_hash,
+                                            })
+                                        }
+                                        Err(err) => serde_json::json!({
+                                            "url": url.as_str(),
+                                            "tx-hash": tx_hash,
+                                            "error": err,
+                                        }),
+                                    }
+                                }
+                                Err(err) => serde_json::json!({
+                                    "url": url.as_str(),
+                                    "error": format!("Send transaction error: {}", err),
+                                }),
+                            }
+                        })
+                        .collect::<Vec<_>>())
+                })?;
+                let resp = serde_json::json!({
+                    "success": success,
+                    "failed": results.len() - success,
+                    "results": results,
+                });
+                if success == 0 {
+                    Err(resp.render(format, color))
+                } else {
+                    Ok(resp.render(format, color))
+                }
+            }
+            _ => Err(matches.usage().to_owned()),
+        }
+    }
+}
+
+/// Prompt before broadcasting, anything but `yes` aborts
+fn confirm_send() -> Result<(), String> {
+    eprint!("Send this transaction? (yes/no): ");
+    io::stderr().flush().map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    if answer.trim() == "yes" {
+        Ok(())
+    } else {
+        Err("Send aborted".to_owned())
+    }
+}
+
+/// A node still syncing may drop the transaction silently, refuse to send to it unless `force`
+fn check_send_sync_state(rpc_client: &mut HttpRpcClient, force: bool) -> Result<(), String> {
+    if force {
+        return Ok(());
+    }
+    let state = get_node_sync_state(rpc_client)?;
+    if !state.is_behind() {
+        return Ok(());
+    }
+    let best_known = estimate_best_known_number(rpc_client, &state)?;
+    Err(format!(
+        "The node is not synced: tip block {}, best known block ~{} (tip {} minutes ago, initial block download: {}). Refuse to send to a node not synced (use --force to send anyway)",
+        state.tip_number,
+        best_known,
+        state.tip_age / 1000 / 60,
+        state.is_initial_block_download,
+    ))
+}
+
+/// Block interval (milliseconds) assumed when the current epoch has no block after its first
+const DEFAULT_BLOCK_INTERVAL: u64 = 8 * 1000;
+
+/// The node can not tell the best known block of the network, estimate it from the tip age
+/// and the average block interval of the current epoch
+fn estimate_best_known_number(
+    rpc_client: &mut HttpRpcClient,
+    state: &NodeSyncState,
+) -> Result<u64, String> {
+    let epoch = rpc_client
+        .get_current_epoch()
+        .call()
+        .map_err(|err| format!("Send get_current_epoch error: {}", err))?;
+    let start_number = epoch.start_number.value();
+    let interval = if state.tip_number > start_number {
+        let start_header = rpc_client
+            .get_header_by_number(BlockNumber::from(start_number))
+            .call()
+            .map_err(|err| format!("Send get_header_by_number error: {}", err))?
+            .0
+            .ok_or_else(|| format!("Can not get header of block {}", start_number))?;
+        let elapsed = state
+            .tip_timestamp
+            .saturating_sub(start_header.inner.timestamp.value());
+        (elapsed / (state.tip_number - start_number)).max(1)
+    } else {
+        DEFAULT_BLOCK_INTERVAL
+    };
+    Ok(state.tip_number + state.tip_age / interval)
+}
+
+fn append_broadcast_log(
+    path: &Path,
+    node: &str,
+    tx_hash: &H256,
+    fee: u64,
+    size: u64,
+) -> Result<(), String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64;
+    let line = serde_json::json!({
+        "timestamp": timestamp,
+        "tx-hash": tx_hash,
+        "fee": fee,
+        "size": size,
+        "node": node,
+    });
+    let mut log_file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| format!("Open broadcast log {:?} error: {}", path, err))?;
+    writeln!(log_file, "{}", line)
+        .map_err(|err| format!("Write broadcast log {:?} error: {}", path, err))
+}
+
+/// Fetch the sent transaction back from the node and compare the serialized bytes
+fn check_sent_transaction(
+    rpc_client: &mut HttpRpcClient,
+    tx_hash: &H256,
+    tx: &Transaction,
+) -> Result<(), String> {
+    let tx_with_status = rpc_client
+        .get_transaction(tx_hash.clone())
+        .call()
+        .map_err(|err| format!("Send get_transaction error: {}", err))?
+        .0
+        .ok_or_else(|| format!("Sent transaction not found in the node: {:#x}", tx_hash))?;
+    let node_tx: Transaction = tx_with_status.transaction.inner.into();
+    if node_tx.as_slice() != tx.as_slice() {
+        return Err(format!(
+            "Sent transaction mismatch, node's (witness hash: {:#x}) != sent (witness hash: {:#x})",
+            node_tx.calc_witness_hash(),
+            tx.calc_witness_hash(),
+        ));
+    }
+    Ok(())
+}
+
+/// Pipe the core transaction (json) to the hook command, read back the transaction to send
+fn run_pre_send_hook(hook: &str, mock_tx: &MockTransaction) -> Result<Transaction, String> {
+    let args = shell_words::split(hook).map_err(|err| format!("Invalid hook command: {}", err))?;
+    let (program, args) = args
+        .split_first()
+        .ok_or_else(|| "Hook command is empty".to_owned())?;
+    let rpc_tx: RpcTransaction = mock_tx.core_transaction().data().into();
+    let input = serde_json::to_string_pretty(&rpc_tx).map_err(|err| err.to_string())?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Run hook command error: {}", err))?;
+    child
+        .stdin
+        .take()
+        .expect("hook stdin is piped")
+        .write_all(input.as_bytes())
+        .map_err(|err| format!("Write to hook command error: {}", err))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Run hook command error: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("Hook command failed: {}", output.status));
+    }
+    let rpc_tx: RpcTransaction = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("Parse transaction from hook command error: {}", err))?;
+    Ok(rpc_tx.into())
+}
+
+/// Resolve `--at-block` (block number or block hash) to block number
+fn resolve_block_number(rpc_client: &mut HttpRpcClient, input: &str) -> Result<u64, String> {
+    if let Ok(number) = input.parse::<u64>() {
+        return Ok(number);
+    }
+    let block_hash: H256 = FixedHashParser::<H256>::default().parse(input)?;
+    rpc_client
+        .get_header(block_hash.clone())
+        .call()
+        .map_err(|err| err.to_string())?
+        .0
+        .map(|header| header.inner.number.value())
+        .ok_or_else(|| format!("Block header not found: {:#x}", block_hash))
+}
+
+/// Genesis info from a saved genesis block (json or yaml)
+fn load_genesis_file(path: &Path) -> Result<GenesisInfo, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Read genesis file {:?} error: {}", path, err))?;
+    let block: RpcBlockView = serde_yaml::from_str(&content).map_err(|err| {
+        format!(
+            "Invalid genesis file {:?}, expect the genesis block (rpc get_block_by_number --number 0): {}",
+            path, err
+        )
+    })?;
+    GenesisInfo::from_block(&block.into())
+        .map_err(|err| format!("Invalid genesis file {:?}: {}", path, err))
+}
+
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "gz").unwrap_or(false)
+}
+
+/// Remove the inputs at `stale_indices` along with their witnesses and append `new_out_points`
+/// as inputs, witnesses of the new inputs are filled when signing
+fn replace_inputs(
+    tx: &Transaction,
+    stale_indices: &[usize],
+    new_out_points: &[OutPoint],
+) -> Transaction {
+    let inputs_len = tx.raw().inputs().len();
+    let kept = |idx: &usize| *idx < inputs_len && !stale_indices.contains(idx);
+    let inputs = tx
+        .raw()
+        .inputs()
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| kept(idx))
+        .map(|(_, input)| input)
+        .chain(
+            new_out_points
+                .iter()
+                .map(|out_point| CellInput::new(out_point.clone(), 0)),
+        )
+        .collect();
+    let witnesses = tx
+        .witnesses()
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| kept(idx))
+        .map(|(_, witness)| witness)
+        .collect();
+    tx.as_advanced_builder()
+        .set_inputs(inputs)
+        .set_witnesses(witnesses)
+        .build()
+        .data()
+}
+
+/// Every lock and type script of the inputs and outputs must use an allowed code hash
+fn check_allowed_code_hashes<C>(
+    mock_tx: &MockTransaction,
+    allowed_code_hashes: &HashSet<H256>,
+    mut live_cell_getter: C,
+) -> Result<(), String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx = mock_tx.core_transaction();
+    let mut scripts = Vec::new();
+    for (idx, input) in tx.inputs().into_iter().enumerate() {
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        scripts.push((format!("inputs[{}].lock", idx), output.lock()));
+        if let Some(script) = output.type_().to_opt() {
+            scripts.push((format!("inputs[{}].type", idx), script));
+        }
+    }
+    for (idx, output) in tx.outputs().into_iter().enumerate() {
+        scripts.push((format!("outputs[{}].lock", idx), output.lock()));
+        if let Some(script) = output.type_().to_opt() {
+            scripts.push((format!("outputs[{}].type", idx), script));
+        }
+    }
+    let errors = scripts
+        .into_iter()
+        .filter_map(|(name, script)| {
+            let code_hash: H256 = script.code_hash().unpack();
+            if allowed_code_hashes.contains(&code_hash) {
+                None
+            } else {
+                Some(format!(
+                    "{}: code_hash {:#x} is not allowed",
+                    name, code_hash
+                ))
+            }
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+fn cell_dep_desc(cell_dep: &CellDep) -> String {
+    let dep_type: DepType = cell_dep.dep_type().unpack();
+    let index: u32 = cell_dep.out_point().index().unpack();
+    format!(
+        "{:#x}-{} ({})",
+        cell_dep.out_point().tx_hash(),
+        index,
+        if dep_type == DepType::DepGroup {
+            "dep_group"
+        } else {
+            "code"
+        }
+    )
+}
+
+/// Cell deps not providing the code of any script run by the transaction (input locks,
+/// input types and output types), a dep group is used if any of its cells is. A script may
+/// still load them as data.
+fn unused_cell_deps<C>(
+    mock_tx: &MockTransaction,
+    mut live_cell_getter: C,
+) -> Result<Vec<CellDep>, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx = mock_tx.core_transaction();
+    let mut scripts = Vec::new();
+    for input in tx.inputs().into_iter() {
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        scripts.push(output.lock());
+        scripts.extend(output.type_().to_opt());
+    }
+    scripts.extend(
+        tx.outputs()
+            .into_iter()
+            .filter_map(|output| output.type_().to_opt()),
+    );
+    let provides_code = |output: &CellOutput, data: &Bytes| {
+        let data_hash = CellOutput::calc_data_hash(data);
+        let type_hash = output
+            .type_()
+            .to_opt()
+            .map(|script| script.calc_script_hash());
+        scripts.iter().any(|script| {
+            let hash_type: ScriptHashType = script.hash_type().unpack();
+            match hash_type {
+                ScriptHashType::Data => script.code_hash() == data_hash,
+                ScriptHashType::Type => Some(script.code_hash()) == type_hash,
+            }
+        })
+    };
+
+    let mut unused = Vec::new();
+    for cell_dep in tx.cell_deps().into_iter() {
+        let (output, data) = mock_tx
+            .get_dep_cell(&cell_dep.out_point(), &mut live_cell_getter)?
+            .ok_or_else(|| format!("cell dep not found: {}", cell_dep))?;
+        let dep_type: DepType = cell_dep.dep_type().unpack();
+        let used = if dep_type == DepType::DepGroup {
+            let mut used = false;
+            for sub_out_point in OutPointVec::from_slice(&data)
+                .map_err(|err| format!("Parse dep group data error: {}", err))?
+                .into_iter()
+            {
+                let (sub_output, sub_data) = mock_tx
+                    .get_dep_cell(&sub_out_point, &mut live_cell_getter)?
+                    .ok_or_else(|| format!("(dep group) cell dep not found: {}", sub_out_point))?;
+                used = used || provides_code(&sub_output, &sub_data);
+            }
+            used
+        } else {
+            provides_code(&output, &data)
+        };
+        if !used {
+            unused.push(cell_dep);
+        }
+    }
+    Ok(unused)
+}
+
+/// Code hashes of the `data` hash type scripts (input locks and types, output types) no cell
+/// dep in the mock info provides, the cells holding them are to be loaded
+fn expected_data_hashes(mock_tx: &MockTransaction) -> Vec<H256> {
+    let tx = mock_tx.core_transaction();
+    let mut scripts = Vec::new();
+    for input in tx.inputs().into_iter() {
+        if let Ok(Some((output, _))) = mock_tx.get_input_cell(&input, |_| Ok(None)) {
+            scripts.push(output.lock());
+            scripts.extend(output.type_().to_opt());
+        }
+    }
+    scripts.extend(
+        tx.outputs()
+            .into_iter()
+            .filter_map(|output| output.type_().to_opt()),
+    );
+    let provided = mock_tx
+        .mock_info
+        .cell_deps
+        .iter()
+        .map(|mock_cell_dep| CellOutput::calc_data_hash(&mock_cell_dep.data))
+        .collect::<HashSet<_>>();
+    let mut data_hashes = Vec::new();
+    for script in scripts {
+        let hash_type: ScriptHashType = script.hash_type().unpack();
+        let code_hash = script.code_hash();
+        if hash_type == ScriptHashType::Data && !provided.contains(&code_hash) {
+            let code_hash: H256 = code_hash.unpack();
+            if !data_hashes.contains(&code_hash) {
+                data_hashes.push(code_hash);
+            }
+        }
+    }
+    data_hashes
+}
+
+fn remove_cell_deps(tx: &Transaction, removed: &[CellDep]) -> Transaction {
+    let cell_deps = tx
+        .raw()
+        .cell_deps()
+        .into_iter()
+        .filter(|cell_dep| !removed.contains(cell_dep))
+        .collect::<Vec<_>>();
+    tx.as_advanced_builder()
+        .set_cell_deps(cell_deps)
+        .build()
+        .data()
+}
+
+/// Every input to sign must exist and be a secp256k1 lock whose key is in key store
+fn check_sign_indices<C>(
+    mock_tx: &MockTransaction,
+    sign_indices: &HashSet<usize>,
+    genesis_info: &GenesisInfo,
+    key_lock_args: &HashSet<H160>,
+    mut live_cell_getter: C,
+) -> Result<(), String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let inputs = mock_tx.tx.raw().inputs();
+    let mut indices = sign_indices.iter().cloned().collect::<Vec<_>>();
+    indices.sort();
+    for idx in indices {
+        let input = inputs.get(idx).ok_or_else(|| {
+            format!(
+                "Sign index {} out of range, the transaction has {} inputs",
+                idx,
+                inputs.len()
+            )
+        })?;
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        let lock = output.lock();
+        let lock_arg = if &lock.code_hash() == genesis_info.secp_type_hash() {
+            H160::from_slice(&lock.args().raw_data()).ok()
+        } else {
+            None
+        };
+        match lock_arg {
+            Some(lock_arg) if key_lock_args.contains(&lock_arg) => {}
+            Some(lock_arg) => {
+                return Err(format!(
+                    "Can not sign input #{}: lock_arg {:#x} is not in key store",
+                    idx, lock_arg
+                ))
+            }
+            None => return Err(format!("Can not sign input #{}: not a secp256k1 lock", idx)),
+        }
+    }
+    Ok(())
+}
+
+/// Check the change output lock is a secp256k1 lock controlled by the user
+fn check_change_lock(
+    lock: &Script,
+    genesis_info: &GenesisInfo,
+    controlled_lock_args: &HashSet<H160>,
+) -> Result<(), String> {
+    let hash_type: ScriptHashType = lock.hash_type().unpack();
+    if &lock.code_hash() != genesis_info.secp_type_hash()
+        || hash_type != ScriptHashType::Type
+        || lock.args().raw_data().len() != 20
+    {
+        return Err(format!(
+            "Change output lock is not a secp256k1 lock (lock hash: {:#x}), please specify --change-lock-arg",
+            lock.calc_script_hash()
+        ));
+    }
+    let lock_arg = H160::from_slice(&lock.args().raw_data()).expect("Convert to H160 failed");
+    if !controlled_lock_args.contains(&lock_arg) {
+        return Err(format!(
+            "Change output lock arg {:x} is not in key store, please specify --change-lock-arg",
+            lock_arg
+        ));
+    }
+    Ok(())
+}
+
+/// Size of the transaction in a block
+fn tx_size(tx: &Transaction) -> u64 {
+    // The transaction is prefixed with a 4 bytes offset in the block
+    tx.as_slice().len() as u64 + 4
+}
+
+/// Fee rate (shannons/KB) of `fee` shannons paid for `size` bytes
+fn fee_rate(fee: u64, size: u64) -> Result<u64, String> {
+    fee.checked_mul(1000)
+        .map(|fee| fee / size)
+        .ok_or_else(|| format!("Fee rate overflow: fee {} shannons", fee))
+}
+
+/// The transaction fee: inputs capacity - outputs capacity
+fn tx_fee<C>(mock_tx: &MockTransaction, mut live_cell_getter: C) -> Result<u64, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx = mock_tx.core_transaction();
+    let mut input_capacities = Vec::new();
+    for input in tx.inputs().into_iter() {
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        input_capacities.push(Unpack::<u64>::unpack(&output.capacity()));
+    }
+    let input_total = sum_capacities(input_capacities, "inputs")?;
+    let output_total = sum_capacities(
+        tx.outputs()
+            .into_iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity())),
+        "outputs",
+    )?;
+    input_total.checked_sub(output_total).ok_or_else(|| {
+        format!(
+            "input total({}) < output total({})",
+            input_total, output_total
+        )
+    })
+}
+
+/// Attach the data read from stdin to the output selected by `--output-data-stdin`
+fn attach_stdin_output_data(m: &ArgMatches, mock_tx: &mut MockTransaction) -> Result<(), String> {
+    let index: usize =
+        match FromStrParser::<usize>::default().from_matches_opt(m, "output-data-stdin", false)? {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+    let mut outputs_data: Vec<_> = mock_tx.tx.raw().outputs_data().into_iter().collect();
+    if index >= outputs_data.len() {
+        return Err(format!(
+            "output index out of range: {}, outputs count: {}",
+            index,
+            outputs_data.len()
+        ));
+    }
+    let mut data = Vec::new();
+    io::stdin()
+        .read_to_end(&mut data)
+        .map_err(|err| format!("Read output data from stdin error: {}", err))?;
+    outputs_data[index] = Bytes::from(data).pack();
+    mock_tx.tx = mock_tx
+        .tx
+        .as_advanced_builder()
+        .set_outputs_data(outputs_data)
+        .build()
+        .data();
+    Ok(())
+}
+
+/// Add `_resolved` (capacity and lock of the input cell from mock info) to every input of the
+/// rendered transaction, remove them before loading the file as a mock transaction again
+fn annotate_inputs(value: &mut serde_json::Value, mock_tx: &MockTransaction) {
+    let inputs = match value["tx"]["inputs"].as_array_mut() {
+        Some(inputs) => inputs,
+        None => return,
+    };
+    for (input_value, input) in inputs.iter_mut().zip(mock_tx.tx.raw().inputs().into_iter()) {
+        let mock_input = mock_tx
+            .mock_info
+            .inputs
+            .iter()
+            .find(|mock_input| mock_input.input.previous_output() == input.previous_output());
+        if let Some(mock_input) = mock_input {
+            let capacity: u64 = mock_input.output.capacity().unpack();
+            let lock: ckb_jsonrpc_types::Script = mock_input.output.lock().into();
+            input_value["_resolved"] = serde_json::json!({
+                "note": "informational, not part of the transaction",
+                "capacity": format!("{:#x}", capacity),
+                "lock": lock,
+                "lock_hash": format!("{:#x}", mock_input.output.lock().calc_script_hash()),
+            });
+        }
+    }
+}
+
+/// Patch the changes of the edited mock transaction into the original --tx-file content
+fn preserve_format(m: &ArgMatches, mock_tx: &MockTransaction) -> Result<String, String> {
+    let tx_file = m
+        .value_of("tx-file")
+        .ok_or_else(|| "<tx-file> is required".to_owned())?;
+    let path = PathBuf::from(tx_file);
+    if is_url(tx_file) || is_gzip_path(&path) {
+        return Err("only a local uncompressed file can be patched".to_owned());
+    }
+    let source = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let strict_parse = m.is_present("strict-parse");
+    let to_value = |mock_tx: MockTransaction| {
+        serde_json::to_value(ReprMockTransaction::from(mock_tx)).map_err(|err| err.to_string())
+    };
+    let base = to_value(parse_mock_tx(&source, tx_file, strict_parse)?)?;
+    let new = to_value(mock_tx.clone())?;
+    let patched = preserve::patch_yaml(&source, &base, &new)?;
+    // Never write a file not loading back to the edited transaction
+    if to_value(parse_mock_tx(&patched, tx_file, strict_parse)?)? != new {
+        return Err("the patched file does not match the edited transaction".to_owned());
+    }
+    Ok(patched)
+}
+
+/// Replace the out point of matching cell deps, return how many are replaced. The mock cell
+/// of the old out point is removed, the new one is resolved from node (or added by hand).
+fn replace_cell_dep(
+    mock_tx: &mut MockTransaction,
+    old_out_point: &OutPoint,
+    new_out_point: &OutPoint,
+) -> usize {
+    let mut replaced = 0;
+    let cell_deps = mock_tx
+        .tx
+        .raw()
+        .cell_deps()
+        .into_iter()
+        .map(|cell_dep| {
+            if &cell_dep.out_point() == old_out_point {
+                replaced += 1;
+                cell_dep
+                    .as_builder()
+                    .out_point(new_out_point.clone())
+                    .build()
+            } else {
+                cell_dep
+            }
+        })
+        .collect::<Vec<_>>();
+    mock_tx.tx = mock_tx
+        .tx
+        .as_advanced_builder()
+        .set_cell_deps(cell_deps)
+        .build()
+        .data();
+    mock_tx
+        .mock_info
+        .cell_deps
+        .retain(|mock_cell_dep| &mock_cell_dep.cell_dep.out_point() != old_out_point);
+    replaced
+}
+
+/// Replace the witness at `index`, the witnesses are padded with empty witness when needed
+fn set_witness(tx: &Transaction, index: usize, witness: Bytes) -> Result<Transaction, String> {
+    let inputs_len = tx.raw().inputs().len();
+    let mut witnesses: Vec<_> = tx.witnesses().into_iter().collect();
+    if index >= inputs_len && index > witnesses.len() {
+        return Err(format!(
+            "witness index out of range: {}, inputs count: {}, witnesses count: {}",
+            index,
+            inputs_len,
+            witnesses.len()
+        ));
+    }
+    while witnesses.len() <= index {
+        witnesses.push(Bytes::new().pack());
+    }
+    witnesses[index] = witness.pack();
+    Ok(tx
+        .as_advanced_builder()
+        .set_witnesses(witnesses)
+        .build()
+        .data())
+}
+
+/// Set the lock of the outputs at `indices`, capacity, type and data are kept
+fn replace_output_locks(
+    tx: &Transaction,
+    indices: &[usize],
+    lock: &Script,
+) -> Result<Transaction, String> {
+    let mut outputs: Vec<_> = tx.raw().outputs().into_iter().collect();
+    for index in indices {
+        let output = outputs.get_mut(*index).ok_or_else(|| {
+            format!(
+                "output index out of range: {}, outputs count: {}",
+                index,
+                tx.raw().outputs().len()
+            )
+        })?;
+        *output = output.clone().as_builder().lock(lock.clone()).build();
+    }
+    Ok(tx.as_advanced_builder().set_outputs(outputs).build().data())
+}
+
+/// `order[i]` is the old index of the new output i, it must list every output exactly once
+fn reorder_outputs(tx: &Transaction, order: &[usize]) -> Result<Transaction, String> {
+    let outputs: Vec<_> = tx.raw().outputs().into_iter().collect();
+    let outputs_data: Vec<_> = tx.raw().outputs_data().into_iter().collect();
+    let mut seen = HashSet::new();
+    for index in order {
+        if *index >= outputs.len() {
+            return Err(format!(
+                "output index out of range: {}, outputs count: {}",
+                index,
+                outputs.len()
+            ));
+        }
+        if !seen.insert(*index) {
+            return Err(format!("output index {} appears more than once", index));
+        }
+    }
+    if seen.len() != outputs.len() {
+        let missing = (0..outputs.len())
+            .filter(|index| !seen.contains(index))
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>();
+        return Err(format!(
+            "order is not a permutation of the outputs, missing: {}",
+            missing.join(",")
+        ));
+    }
+    if outputs_data.len() != outputs.len() {
+        return Err(format!(
+            "outputs count {} != outputs_data count {}",
+            outputs.len(),
+            outputs_data.len()
+        ));
+    }
+    Ok(tx
+        .as_advanced_builder()
+        .set_outputs(order.iter().map(|index| outputs[*index].clone()).collect())
+        .set_outputs_data(
+            order
+                .iter()
+                .map(|index| outputs_data[*index].clone())
+                .collect(),
+        )
+        .build()
+        .data())
+}
+
+/// Mock transaction file extensions accepted by verify-all
+fn is_mock_tx_path(path: &Path) -> bool {
+    let path = if is_gzip_path(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    };
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") | Some("yaml") | Some("yml") | Some("json5") => true,
+        _ => false,
+    }
+}
+
+fn load_mock_tx(m: &ArgMatches) -> Result<MockTransaction, String> {
+    let tx_bytes_opt: Option<Vec<u8>> = match HexParser.from_matches_opt(m, "tx-hex", false)? {
+        Some(tx_hex) => Some(tx_hex),
+        None => Base64Parser.from_matches_opt(m, "tx-base64", false)?,
+    };
+    if let Some(tx_bytes) = tx_bytes_opt {
+        let tx = Transaction::from_slice(&tx_bytes)
+            .map_err(|err| format!("Invalid serialized transaction: {}", err))?;
+        return Ok(MockTransaction {
+            mock_info: MockInfo::default(),
+            tx,
+        });
+    }
+    let tx_file = m
+        .value_of("tx-file")
+        .ok_or_else(|| "<tx-file> is required".to_owned())?;
+    let mut mock_tx = if is_url(tx_file) {
+        let url = UrlParser.parse(tx_file)?;
+        let timeout: u64 = FromStrParser::<u64>::default().from_matches(m, "download-timeout")?;
+        download_mock_tx(
+            &url,
+            Duration::from_secs(timeout),
+            m.is_present("strict-parse"),
+        )?
+    } else {
+        let path: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+        load_mock_tx_file(&path, m.is_present("strict-parse"))?
+    };
+    let cells_path: Option<PathBuf> =
+        FilePathParser::new(true).from_matches_opt(m, "cells-file", false)?;
+    if let Some(cells_path) = cells_path {
+        let cells_tx = load_mock_tx_file(&cells_path, m.is_present("strict-parse"))?;
+        merge_mock_info(&mut mock_tx.mock_info, cells_tx.mock_info);
+    }
+    Ok(mock_tx)
+}
+
+/// Add the cells and headers not yet in `mock_info`, existing ones take precedence
+fn merge_mock_info(mock_info: &mut MockInfo, other: MockInfo) {
+    for mock_input in other.inputs {
+        if mock_info
+            .inputs
+            .iter()
+            .all(|existing| existing.input != mock_input.input)
+        {
+            mock_info.inputs.push(mock_input);
+        }
+    }
+    for mock_cell_dep in other.cell_deps {
+        if mock_info
+            .cell_deps
+            .iter()
+            .all(|existing| existing.cell_dep.out_point() != mock_cell_dep.cell_dep.out_point())
+        {
+            mock_info.cell_deps.push(mock_cell_dep);
+        }
+    }
+    for header in other.header_deps {
+        if mock_info
+            .header_deps
+            .iter()
+            .all(|existing| existing.hash() != header.hash())
+        {
+            mock_info.header_deps.push(header);
+        }
+    }
+}
+
+fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Fetch a mock transaction file, gzip compressed if the url path ends with .gz
+fn download_mock_tx(
+    url: &Url,
+    timeout: Duration,
+    strict_parse: bool,
+) -> Result<MockTransaction, String> {
+    let mut resp = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|err| err.to_string())?
+        .get(url.as_str())
+        .send()
+        .map_err(|err| format!("GET {} error: {}", url, err))?;
+    if !resp.status().is_success() {
+        return Err(format!("GET {} failed: {}", url, resp.status()));
+    }
+    let mut body = Vec::new();
+    resp.copy_to(&mut body)
+        .map_err(|err| format!("GET {} error: {}", url, err))?;
+    let content = if is_gzip_path(Path::new(url.path())) {
+        let mut content = String::new();
+        GzDecoder::new(&body[..])
+            .read_to_string(&mut content)
+            .map_err(|err| err.to_string())?;
+        content
+    } else {
+        String::from_utf8(body).map_err(|err| err.to_string())?
+    };
+    parse_mock_tx(&content, url.as_str(), strict_parse)
+}
+
+fn load_mock_tx_file(path: &Path, strict_parse: bool) -> Result<MockTransaction, String> {
+    let mut content = String::new();
+    let file = fs::File::open(path).map_err(|err| err.to_string())?;
+    let read_result = if is_gzip_path(path) {
+        GzDecoder::new(file).read_to_string(&mut content)
+    } else {
+        let mut file = file;
+        file.read_to_string(&mut content)
+    };
+    read_result.map_err(|err| err.to_string())?;
+    parse_mock_tx(&content, &format!("{:?}", path), strict_parse)
+}
+
+/// In `strict_parse` mode any unknown field (e.g. a typo) in the content is an error
+fn parse_mock_tx(
+    content: &str,
+    source: &str,
+    strict_parse: bool,
+) -> Result<MockTransaction, String> {
+    // JSON5 (comments, trailing commas) for hand edited files
+    let mut value: serde_json::Value = serde_yaml::from_str(content)
+        .map_err(|err| err.to_string())
+        .or_else(|_| serde_json::from_str(content).map_err(|err| err.to_string()))
+        .or_else(|_| json5::from_str(content).map_err(|err| err.to_string()))?;
+    capacity::parse_capacities(&mut value)?;
+    if strict_parse {
+        let mut unknown_fields = Vec::new();
+        let repr_tx: ReprMockTransaction =
+            serde_ignored::deserialize(value, |path| unknown_fields.push(path.to_string()))
+                .map_err(|err| err.to_string())?;
+        if !unknown_fields.is_empty() {
+            return Err(format!(
+                "Unknown fields in {}: {}",
+                source,
+                unknown_fields.join(", ")
+            ));
+        }
+        return Ok(repr_tx.into());
+    }
+    let repr_tx: ReprMockTransaction =
+        serde_json::from_value(value).map_err(|err| err.to_string())?;
+    Ok(repr_tx.into())
+}
+
+/// Verify the scripts of the mock transaction, explain the error if it is a known one
+fn verify_scripts<L: MockResourceLoader>(
+    mock_tx: &mut MockTransaction,
+    max_cycles: u64,
+    mut loader: L,
+    skip_headers: bool,
+    only: Option<ScriptGroupKind>,
+) -> Result<u64, String> {
+    let mut helper = MockTransactionHelper::new(mock_tx);
+    helper.check_script_hash_types(|out_point| loader.get_live_cell(out_point))?;
+    helper
+        .verify_groups(max_cycles, loader, skip_headers, only)
+        .map_err(|err| match explain::explain_error(&err) {
+            Some(explanation) => format!("{}\nExplanation: {}", err, explanation),
+            None => err,
+        })
+}
+
+/// Verify every script group of the mock transaction independently, all the failing groups
+/// (including those consuming more than `group_max_cycles`) are reported instead of the first one
+fn verify_script_groups<L: MockResourceLoader>(
+    mock_tx: &mut MockTransaction,
+    max_cycles: u64,
+    group_max_cycles: Option<u64>,
+    mut loader: L,
+    skip_headers: bool,
+    only: Option<ScriptGroupKind>,
+) -> Result<u64, String> {
+    let mut helper = MockTransactionHelper::new(mock_tx);
+    helper.check_script_hash_types(|out_point| loader.get_live_cell(out_point))?;
+    let groups = helper.verify_each_group(max_cycles, loader, skip_headers, only)?;
+    let mut cycles: u64 = 0;
+    let mut errors = Vec::new();
+    for group in groups {
+        match group.result {
+            Ok(cycle) => {
+                if let Some(limit) = group_max_cycles.filter(|limit| cycle > *limit) {
+                    errors.push(format!(
+                        "{} group {:#x}: consumed {} cycles, exceeded per group max cycles {}",
+                        group.kind, group.script_hash, cycle, limit
+                    ));
+                }
+                cycles = cycles.saturating_add(cycle);
+            }
+            Err(err) => {
+                let err = match explain::explain_error(&err) {
+                    Some(explanation) => format!("{}\nExplanation: {}", err, explanation),
+                    None => err,
+                };
+                errors.push(format!(
+                    "{} group {:#x}: {}",
+                    group.kind, group.script_hash, err
+                ));
+            }
+        }
+    }
+    if errors.is_empty() && cycles > max_cycles {
+        errors.push(format!(
+            "Verify script error: exceeded max cycles {}",
+            max_cycles
+        ));
+    }
+    if errors.is_empty() {
+        Ok(cycles)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Check inputs' since against the given chain context, return the inputs not yet spendable.
+/// Relative and timestamp since need the cells' on-chain context, they are not checked here.
+fn check_inputs_since(
+    mock_tx: &MockTransaction,
+    current_block: Option<u64>,
+    current_epoch: Option<EpochNumberWithFraction>,
+) -> Vec<serde_json::Value> {
+    let mut immature_inputs = Vec::new();
+    for (index, input) in mock_tx.core_transaction().inputs().into_iter().enumerate() {
+        let since = Since(input.since().unpack());
+        if since.0 == 0 {
+            continue;
+        }
+        let reason = match (since.is_relative(), since.metric()) {
+            (_, None) => Some("invalid since format".to_owned()),
+            (false, Some(SinceMetric::BlockNumber)) => current_block
+                .filter(|number| *number < since.value())
+                .map(|number| format!("current block {} < since block {}", number, since.value())),
+            (false, Some(SinceMetric::EpochNumber)) => {
+                let epoch = EpochNumberWithFraction::from_full_value(since.value());
+                let (since_value, since_length) = epoch_rational(epoch);
+                current_epoch
+                    .filter(|current| {
+                        let (current_value, current_length) = epoch_rational(*current);
+                        current_value * since_length < since_value * current_length
+                    })
+                    .map(|current| {
+                        format!(
+                            "current epoch {} < since epoch {}",
+                            format_epoch(current),
+                            format_epoch(epoch)
+                        )
+                    })
+            }
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            immature_inputs.push(serde_json::json!({
+                "index": index,
+                "since": format!("{:#x}", since.0),
+                "reason": reason,
+            }));
+        }
+    }
+    immature_inputs
+}
+
+/// Check inputs' relative since (block number and epoch number) against the node's tip, the
+/// since is counted from the block committing the input cell. Return the inputs not yet
+/// spendable with the block/epoch they become spendable.
+fn check_inputs_relative_since(
+    rpc_client: &mut HttpRpcClient,
+    mock_tx: &MockTransaction,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut immature_inputs = Vec::new();
+    let mut tip_opt: Option<HeaderView> = None;
+    for (index, input) in mock_tx.core_transaction().inputs().into_iter().enumerate() {
+        let since = Since(input.since().unpack());
+        if !since.is_relative() {
+            continue;
+        }
+        // Relative timestamp needs the median time of the blocks, not checked here
+        match since.metric() {
+            Some(SinceMetric::Timestamp) | None => continue,
+            Some(_) => (),
+        }
+        let tip = match tip_opt {
+            Some(ref tip) => tip.clone(),
+            None => {
+                let tip: HeaderView = rpc_client
+                    .get_tip_header()
+                    .call()
+                    .map_err(|err| format!("Send get_tip_header error: {}", err))?
+                    .into();
+                tip_opt = Some(tip.clone());
+                tip
+            }
+        };
+        let prev_tx_hash: H256 = input.previous_output().tx_hash().unpack();
+        let block_hash = rpc_client
+            .get_transaction(prev_tx_hash.clone())
+            .call()
+            .map_err(|err| format!("Send get_transaction error: {}", err))?
+            .0
+            .and_then(|tx_with_status| tx_with_status.tx_status.block_hash);
+        let block_hash = match block_hash {
+            Some(block_hash) => block_hash,
+            None => {
+                immature_inputs.push(serde_json::json!({
+                    "index": index,
+                    "since": format!("{:#x}", since.0),
+                    "reason": format!(
+                        "input cell is not committed (transaction {:#x})",
+                        prev_tx_hash
+                    ),
+                }));
+                continue;
+            }
+        };
+        let created: HeaderView = rpc_client
+            .get_header(block_hash.clone())
+            .call()
+            .map_err(|err| format!("Send get_header error: {}", err))?
+            .0
+            .ok_or_else(|| format!("Block header not found: {:#x}", block_hash))?
+            .into();
+        if let Some((reason, spendable_at)) = relative_since_immaturity(since, &created, &tip) {
+            immature_inputs.push(serde_json::json!({
+                "index": index,
+                "since": format!("{:#x}", since.0),
+                "reason": reason,
+                "spendable-at": spendable_at,
+            }));
+        }
+    }
+    Ok(immature_inputs)
+}
+
+/// Check the relative `since` of an input created in block `created` against `tip`, return the
+/// reason and the block number or epoch it becomes spendable at if it is not spendable yet
+fn relative_since_immaturity(
+    since: Since,
+    created: &HeaderView,
+    tip: &HeaderView,
+) -> Option<(String, serde_json::Value)> {
+    match since.metric() {
+        Some(SinceMetric::BlockNumber) => {
+            let spendable_at = created.number().saturating_add(since.value());
+            if tip.number() >= spendable_at {
+                return None;
+            }
+            Some((
+                format!(
+                    "tip block {} < block {} (created at block {} + relative {})",
+                    tip.number(),
+                    spendable_at,
+                    created.number(),
+                    since.value()
+                ),
+                serde_json::json!({ "block": spendable_at }),
+            ))
+        }
+        Some(SinceMetric::EpochNumber) => {
+            let relative = EpochNumberWithFraction::from_full_value(since.value());
+            // created + relative as a rational number of epochs
+            let (created_value, created_length) = epoch_rational(created.epoch());
+            let (relative_value, relative_length) = epoch_rational(relative);
+            let length = created_length * relative_length;
+            let value = created_value * relative_length + relative_value * created_length;
+            let (tip_value, tip_length) = epoch_rational(tip.epoch());
+            if tip_value * length >= value * tip_length {
+                return None;
+            }
+            let spendable_at = epoch_from_rational(value, length);
+            Some((
+                format!(
+                    "tip epoch {} < epoch {} (created at epoch {} + relative {})",
+                    format_epoch(tip.epoch()),
+                    format_epoch(spendable_at),
+                    format_epoch(created.epoch()),
+                    format_epoch(relative)
+                ),
+                serde_json::json!({ "epoch": format_epoch(spendable_at) }),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Epoch with fraction as the rational number (number * length + index) / length, an epoch
+/// without length (e.g. a since of whole epochs) has no fraction
+fn epoch_rational(epoch: EpochNumberWithFraction) -> (u128, u128) {
+    let length = u128::from(epoch.length());
+    if length == 0 {
+        (u128::from(epoch.number()), 1)
+    } else {
+        (
+            u128::from(epoch.number()) * length + u128::from(epoch.index()),
+            length,
+        )
+    }
+}
+
+/// Reverse of `epoch_rational`, a fraction too fine for the 16 bits index and length is
+/// rounded up, the epoch is not reached earlier than that
+fn epoch_from_rational(value: u128, length: u128) -> EpochNumberWithFraction {
+    const MAX_LENGTH: u128 = 0xffff;
+    let mut number = value / length;
+    let divisor = gcd(value % length, length);
+    let (mut index, mut length) = ((value % length) / divisor, length / divisor);
+    if length > MAX_LENGTH {
+        index = (index * MAX_LENGTH + length - 1) / length;
+        length = MAX_LENGTH;
+    }
+    if index >= length {
+        number += 1;
+        index = 0;
+        length = 1;
+    }
+    EpochNumberWithFraction::new(number as u64, index as u64, length as u64)
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn format_epoch(epoch: EpochNumberWithFraction) -> String {
+    if epoch.index() == 0 {
+        epoch.number().to_string()
+    } else {
+        format!("{} ({}/{})", epoch.number(), epoch.index(), epoch.length())
+    }
+}
+
+/// Cell dep changes made while completing the transaction, reported in the response instead
+/// of stderr which may carry the `--json-events` stream
+#[derive(Debug, Clone, Default)]
+struct DepChanges {
+    /// System cell deps added for the scripts of the transaction
+    added: Vec<serde_json::Value>,
+    /// Number of duplicate cell deps removed (--deduplicate-deps)
+    duplicates_removed: usize,
+    /// Cell deps providing no script code removed (--prune-deps --no-data-deps)
+    unused_removed: Vec<String>,
+    /// Cell deps providing no script code, kept in case a script loads them as data
+    unused: Vec<String>,
+}
+
+impl DepChanges {
+    fn report(&self, resp: &mut serde_json::Value) {
+        if !self.added.is_empty() {
+            resp["added-cell-deps"] = serde_json::json!(self.added);
+        }
+        if self.duplicates_removed > 0 {
+            resp["removed-duplicate-deps"] = serde_json::json!(self.duplicates_removed);
+        }
+        if !self.unused_removed.is_empty() {
+            resp["removed-cell-deps"] = serde_json::json!(self.unused_removed);
+        }
+        if !self.unused.is_empty() {
+            resp["unused-cell-deps"] = serde_json::json!(self.unused);
+        }
+    }
+}
+
+/// Cells loaded by the loaders of a command, later checks look them up instead of asking the
+/// node again
+#[derive(Debug, Clone, Default)]
+struct ResolvedCells(Rc<RefCell<HashMap<OutPoint, (CellOutput, Option<Bytes>)>>>);
+
+impl ResolvedCells {
+    /// `data` is None for a cell loaded without data, its data recorded before is kept
+    fn record(&self, out_point: OutPoint, output: &CellOutput, data: Option<&Bytes>) {
+        let mut cells = self.0.borrow_mut();
+        match data {
+            Some(data) => {
+                cells.insert(out_point, (output.clone(), Some(data.clone())));
+            }
+            None => {
+                cells
+                    .entry(out_point)
+                    .or_insert_with(|| (output.clone(), None));
+            }
+        }
+    }
+
+    /// The recorded cell, None if it was not loaded with data
+    fn get(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
+        self.0
+            .borrow()
+            .get(out_point)
+            .and_then(|(output, data)| data.clone().map(|data| (output.clone(), data)))
+    }
+
+    fn out_points(&self) -> Vec<OutPoint> {
+        self.0.borrow().keys().cloned().collect()
+    }
+}
+
+struct Loader<'a> {
+    rpc_client: &'a mut HttpRpcClient,
+    /// Directory of cell data files named by their data hash
+    data_store: Option<PathBuf>,
+    /// Do not load header deps from the node
+    skip_headers: bool,
+    /// Resolve cells as of this block number
+    at_block: Option<u64>,
+    /// Load the cell data, capacity/lock only users (e.g. fee calculation) skip it to save
+    /// bandwidth, the cells are then returned with empty data
+    with_data: bool,
+    /// Never ask the node (`verify --cells-file`), every cell and header must be in the mock
+    /// transaction
+    offline: bool,
+    /// Data hashes the transaction needs but its mock info does not provide (see
+    /// `expected_data_hashes`), a cell is loaded with one of them from the data store
+    expected_data_hashes: Vec<H256>,
+    /// Every loaded cell is recorded here
+    resolved_cells: ResolvedCells,
+}
+
+impl<'a> Loader<'a> {
+    fn new(rpc_client: &'a mut HttpRpcClient) -> Loader<'a> {
+        Loader {
+            rpc_client,
+            data_store: None,
+            skip_headers: false,
+            at_block: None,
+            with_data: true,
+            offline: false,
+            expected_data_hashes: Vec::new(),
+            resolved_cells: ResolvedCells::default(),
+        }
+    }
+
+    fn without_data(mut self) -> Loader<'a> {
+        self.with_data = false;
+        self
+    }
+
+    /// Load the cells `mock_tx` needs the code of from the data store
+    fn with_expected_data(mut self, mock_tx: &MockTransaction) -> Loader<'a> {
+        self.expected_data_hashes = expected_data_hashes(mock_tx);
+        self
+    }
+
+    /// The stored data of the cell, the node is asked for the cell without data. Only used
+    /// when exactly one expected data hash not yet taken by another cell is in the store and
+    /// fits the cell's capacity, the cell is loaded with data from the node otherwise.
+    fn get_stored_cell(
+        &mut self,
+        out_point: OutPoint,
+    ) -> Result<Option<(CellOutput, Bytes)>, String> {
+        let output = match self
+            .rpc_client
+            .get_live_cell(out_point.clone().into(), false)
+            .call()
+            .map_err(|err| err.to_string())?
+            .cell
+        {
+            Some(info) => CellOutput::from(info.output),
+            None => return Ok(None),
+        };
+        let capacity: u64 = output.capacity().unpack();
+        let mut candidates = Vec::new();
+        for data_hash in &self.expected_data_hashes {
+            if let Some(data) = self.load_stored_data(data_hash)? {
+                let fits = Capacity::bytes(data.len())
+                    .and_then(|data_capacity| output.occupied_capacity(data_capacity))
+                    .map(|occupied| occupied.as_u64() <= capacity)
+                    .unwrap_or(false);
+                if fits {
+                    candidates.push((data_hash.clone(), data));
+                }
+            }
+        }
+        if candidates.len() == 1 {
+            let (data_hash, data) = candidates.pop().expect("one candidate");
+            self.expected_data_hashes.retain(|hash| hash != &data_hash);
+            return Ok(Some((output, data)));
+        }
+        let data = self
+            .rpc_client
+            .get_live_cell(out_point.into(), true)
+            .call()
+            .map_err(|err| err.to_string())?
+            .cell
+            .and_then(|info| info.data);
+        Ok(Some(match data {
+            Some(data) => {
+                self.expected_data_hashes.retain(|hash| hash != &data.hash);
+                (output, data.content.into_bytes())
+            }
+            None => (output, Bytes::new()),
+        }))
+    }
+
+    /// Load the cell as of block `number`, the cell must be created at or before that block.
+    /// Whether the cell was already spent at that block is not checked, the node does not
+    /// provide historical cell status.
+    fn get_cell_at_block(
+        &mut self,
+        out_point: &OutPoint,
+        number: u64,
+    ) -> Result<Option<(CellOutput, Bytes)>, String> {
+        let tx_with_status = match self
+            .rpc_client
+            .get_transaction(out_point.tx_hash().unpack())
+            .call()
+            .map_err(|err| err.to_string())?
+            .0
+        {
+            Some(tx_with_status) => tx_with_status,
+            None => return Ok(None),
+        };
+        let block_hash = match tx_with_status.tx_status.block_hash {
+            Some(block_hash) => block_hash,
+            // Transaction still in pool
+            None => return Ok(None),
+        };
+        let header = self
+            .rpc_client
+            .get_header(block_hash.clone())
+            .call()
+            .map_err(|err| err.to_string())?
+            .0
+            .ok_or_else(|| format!("Block header not found: {:#x}", block_hash))?;
+        if header.inner.number.value() > number {
+            return Ok(None);
+        }
+        let output_index: u32 = out_point.index().unpack();
+        let tx = tx_with_status.transaction.inner;
+        Ok(tx
+            .outputs
+            .get(output_index as usize)
+            .cloned()
+            .and_then(|output| {
+                tx.outputs_data
+                    .get(output_index as usize)
+                    .map(|data| (CellOutput::from(output), data.clone().into_bytes()))
+            }))
+    }
+
+    /// Load the cell data from data store, return None if the data hash not found in it
+    fn load_stored_data(&self, data_hash: &H256) -> Result<Option<Bytes>, String> {
+        let mut path = match self.data_store {
+            Some(ref dir) => dir.clone(),
+            None => return Ok(None),
+        };
+        path.push(format!("{:#x}", data_hash));
+        if !path.exists() {
+            path.set_file_name(format!("{:x}", data_hash));
+        }
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(&path).map_err(|err| err.to_string())?;
+        let actual_hash: H256 = CellOutput::calc_data_hash(&data).unpack();
+        if &actual_hash != data_hash {
+            return Err(format!(
+                "Data hash mismatch in data store file {:?}, expected: {:#x}, actual: {:#x}",
+                path, data_hash, actual_hash
+            ));
+        }
+        Ok(Some(Bytes::from(data)))
+    }
+
+    fn load_live_cell(
+        &mut self,
+        out_point: OutPoint,
+    ) -> Result<Option<(CellOutput, Bytes)>, String> {
+        if self.offline {
+            return OfflineLoader.get_live_cell(out_point);
+        }
+        if let Some(number) = self.at_block {
+            return self.get_cell_at_block(&out_point, number);
+        }
+        if self.with_data && self.data_store.is_some() && !self.expected_data_hashes.is_empty() {
+            return self.get_stored_cell(out_point);
+        }
+        let cell_info = self
+            .rpc_client
+            .get_live_cell(out_point.into(), self.with_data)
+            .call()
+            .map(|resp| resp.cell)
+            .map_err(|err| err.to_string())?;
+        Ok(cell_info.map(|info| {
+            let data = info
+                .data
+                .map(|data| data.content.into_bytes())
+                .unwrap_or_default();
+            (CellOutput::from(info.output), data)
+        }))
+    }
+}
+
+impl<'a> MockResourceLoader for Loader<'a> {
+    fn get_header(&mut self, hash: H256) -> Result<Option<HeaderView>, String> {
+        if self.skip_headers {
+            return Ok(None);
+        }
+        if self.offline {
+            return OfflineLoader.get_header(hash);
+        }
+        self.rpc_client
+            .get_header(hash)
+            .call()
+            .map(|header_opt| header_opt.0.map(Into::into))
+            .map_err(|err| err.to_string())
+    }
+
+    fn get_live_cell(
+        &mut self,
+        out_point: OutPoint,
+    ) -> Result<Option<(CellOutput, Bytes)>, String> {
+        let cell = self.load_live_cell(out_point.clone())?;
+        if let Some((ref output, ref data)) = cell {
+            let data = if self.with_data { Some(data) } else { None };
+            self.resolved_cells.record(out_point, output, data);
+        }
+        Ok(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_crypto::secp::SECP256K1;
+    use ckb_hash::blake2b_256;
+    use ckb_sdk::wallet::ScryptType;
+    use ckb_types::core::BlockView;
+    use std::{env, iter, process};
+
+    #[test]
+    fn test_replace_inputs() {
+        let out_point = |index| OutPoint::new(h256!("0xff01").pack(), index);
+        let witness = |byte| Bytes::from(vec![byte]).pack();
+        // The last input is not signed yet
+        let tx = TransactionBuilder::default()
+            .inputs((0..3).map(|index| CellInput::new(out_point(index), 0)))
+            .witness(witness(0))
+            .witness(witness(1))
+            .build()
+            .data();
+        let previous_outputs = |tx: &Transaction| {
+            tx.raw()
+                .inputs()
+                .into_iter()
+                .map(|input| input.previous_output())
+                .collect::<Vec<_>>()
+        };
+
+        let replaced = replace_inputs(&tx, &[1], &[out_point(3)]);
+        assert_eq!(
+            previous_outputs(&replaced),
+            vec![out_point(0), out_point(2), out_point(3)]
+        );
+        assert_eq!(
+            replaced.witnesses().into_iter().collect::<Vec<_>>(),
+            vec![witness(0)]
+        );
+
+        let replaced = replace_inputs(&tx, &[0, 2], &[]);
+        assert_eq!(previous_outputs(&replaced), vec![out_point(1)]);
+        assert_eq!(
+            replaced.witnesses().into_iter().collect::<Vec<_>>(),
+            vec![witness(1)]
+        );
+    }
+
+    #[test]
+    fn test_unused_cell_deps() {
+        let code = Bytes::from("code");
+        let lock = Script::new_builder()
+            .code_hash(CellOutput::calc_data_hash(&code))
+            .hash_type(ScriptHashType::Data.pack())
+            .build();
+        let mut mock_tx = MockTransaction::default();
+        let mut push_cell_dep = |tx_hash: H256, index, dep_type: DepType, data: Bytes| {
+            let cell_dep = CellDep::new_builder()
+                .out_point(OutPoint::new(tx_hash.pack(), index))
+                .dep_type(dep_type.pack())
+                .build();
+            mock_tx.mock_info.cell_deps.push(MockCellDep {
+                cell_dep: cell_dep.clone(),
+                output: CellOutput::default(),
+                data,
+            });
+            cell_dep
+        };
+        let code_dep = push_cell_dep(h256!("0xff02"), 0, DepType::Code, code);
+        let data_dep = push_cell_dep(h256!("0xff02"), 1, DepType::Code, Bytes::from("data"));
+        let group_data = |cell_dep: &CellDep| {
+            OutPointVec::new_builder()
+                .push(cell_dep.out_point())
+                .build()
+                .as_bytes()
+        };
+        let code_group =
+            push_cell_dep(h256!("0xff03"), 0, DepType::DepGroup, group_data(&code_dep));
+        let data_group =
+            push_cell_dep(h256!("0xff03"), 1, DepType::DepGroup, group_data(&data_dep));
+        let input = CellInput::new(OutPoint::new(h256!("0xff01").pack(), 0), 0);
+        mock_tx.mock_info.inputs.push(MockInput {
+            input: input.clone(),
+            output: CellOutput::new_builder().lock(lock).build(),
+            data: Bytes::default(),
+        });
+        let cell_deps = vec![
+            code_dep.clone(),
+            data_dep.clone(),
+            code_group.clone(),
+            data_group.clone(),
+        ];
+        mock_tx.tx = TransactionBuilder::default()
+            .input(input)
+            .cell_deps(cell_deps)
+            .build()
+            .data();
+
+        let unused = unused_cell_deps(&mock_tx, |_| Ok(None)).unwrap();
+        assert_eq!(unused, vec![data_dep.clone(), data_group.clone()]);
+        let pruned = remove_cell_deps(&mock_tx.tx, &unused);
+        assert_eq!(
+            pruned.raw().cell_deps().into_iter().collect::<Vec<_>>(),
+            vec![code_dep, code_group]
+        );
+
+        // Cells of the deps must be known
+        mock_tx
+            .mock_info
+            .cell_deps
+            .retain(|mock_cell_dep| mock_cell_dep.cell_dep.out_point() != data_dep.out_point());
+        let err = unused_cell_deps(&mock_tx, |_| Ok(None)).unwrap_err();
+        assert!(err.contains("cell dep not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_reorder_outputs() {
+        let output = |capacity: u64| CellOutput::new_builder().capacity(capacity.pack()).build();
+        let tx = TransactionBuilder::default()
+            .outputs((0..3).map(output))
+            .outputs_data((0..3).map(|byte: u8| Bytes::from(vec![byte]).pack()))
+            .build()
+            .data();
+
+        let reordered = reorder_outputs(&tx, &[2, 0, 1]).unwrap();
+        let capacities = reordered
+            .raw()
+            .outputs()
+            .into_iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+            .collect::<Vec<_>>();
+        assert_eq!(capacities, vec![2, 0, 1]);
+        let outputs_data = reordered
+            .raw()
+            .outputs_data()
+            .into_iter()
+            .map(|data| data.raw_data()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(outputs_data, vec![2, 0, 1]);
+
+        let err = reorder_outputs(&tx, &[0, 1, 3]).unwrap_err();
+        assert!(err.contains("out of range"), "{}", err);
+        let err = reorder_outputs(&tx, &[0, 1, 1]).unwrap_err();
+        assert!(err.contains("more than once"), "{}", err);
+        let err = reorder_outputs(&tx, &[1, 0]).unwrap_err();
+        assert!(err.contains("missing: 2"), "{}", err);
+        let no_data = tx
+            .as_advanced_builder()
+            .set_outputs_data(Vec::new())
+            .build()
+            .data();
+        let err = reorder_outputs(&no_data, &[0, 1, 2]).unwrap_err();
+        assert!(err.contains("outputs_data count"), "{}", err);
+    }
+
+    #[test]
+    fn test_set_witness() {
+        let witness = |byte| Bytes::from(vec![byte]);
+        let input = |index| CellInput::new(OutPoint::new(h256!("0xff01").pack(), index), 0);
+        // Only the first of the 3 inputs is signed
+        let tx = TransactionBuilder::default()
+            .inputs((0..3).map(input))
+            .witness(witness(0).pack())
+            .build()
+            .data();
+        let witnesses = |tx: &Transaction| {
+            tx.witnesses()
+                .into_iter()
+                .map(|witness| witness.raw_data())
+                .collect::<Vec<_>>()
+        };
+
+        let replaced = set_witness(&tx, 0, witness(1)).unwrap();
+        assert_eq!(witnesses(&replaced), vec![witness(1)]);
+        // Padded up to the input
+        let padded = set_witness(&tx, 2, witness(2)).unwrap();
+        assert_eq!(
+            witnesses(&padded),
+            vec![witness(0), Bytes::new(), witness(2)]
+        );
+        // Right after the last witness
+        let appended = set_witness(&padded, 3, witness(3)).unwrap();
+        assert_eq!(witnesses(&appended).len(), 4);
+
+        let err = set_witness(&tx, 3, witness(3)).unwrap_err();
+        assert!(err.contains("out of range"), "{}", err);
+        let err = set_witness(&padded, 4, witness(4)).unwrap_err();
+        assert!(err.contains("out of range"), "{}", err);
+    }
+
+    #[test]
+    fn test_replace_cell_dep() {
+        let old_out_point = OutPoint::new(h256!("0xff02").pack(), 0);
+        let new_out_point = OutPoint::new(h256!("0xff02").pack(), 1);
+        let other_out_point = OutPoint::new(h256!("0xff03").pack(), 0);
+        let cell_dep = |out_point: &OutPoint, dep_type: DepType| {
+            CellDep::new_builder()
+                .out_point(out_point.clone())
+                .dep_type(dep_type.pack())
+                .build()
+        };
+        let mut mock_tx = MockTransaction::default();
+        for out_point in &[&old_out_point, &other_out_point] {
+            mock_tx.mock_info.cell_deps.push(MockCellDep {
+                cell_dep: cell_dep(out_point, DepType::Code),
+                output: CellOutput::default(),
+                data: Bytes::default(),
+            });
+        }
+        mock_tx.tx = TransactionBuilder::default()
+            .cell_dep(cell_dep(&old_out_point, DepType::DepGroup))
+            .cell_dep(cell_dep(&other_out_point, DepType::Code))
+            .build()
+            .data();
+
+        assert_eq!(
+            replace_cell_dep(&mut mock_tx, &old_out_point, &new_out_point),
+            1
+        );
+        // The dep type is kept
+        assert_eq!(
+            mock_tx.tx.raw().cell_deps().into_iter().collect::<Vec<_>>(),
+            vec![
+                cell_dep(&new_out_point, DepType::DepGroup),
+                cell_dep(&other_out_point, DepType::Code),
+            ]
+        );
+        let mock_out_points = |mock_tx: &MockTransaction| {
+            mock_tx
+                .mock_info
+                .cell_deps
+                .iter()
+                .map(|mock_cell_dep| mock_cell_dep.cell_dep.out_point())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(mock_out_points(&mock_tx), vec![other_out_point.clone()]);
+
+        // Nothing matches any more
+        let tx = mock_tx.tx.clone();
+        assert_eq!(
+            replace_cell_dep(&mut mock_tx, &old_out_point, &new_out_point),
+            0
+        );
+        assert_eq!(mock_tx.tx.as_slice(), tx.as_slice());
+        assert_eq!(mock_out_points(&mock_tx), vec![other_out_point]);
+    }
+
+    #[test]
+    fn test_replace_output_locks() {
+        let lock = |byte| {
+            Script::new_builder()
+                .args(Bytes::from(vec![byte]).pack())
+                .build()
+        };
+        let type_script = Script::new_builder()
+            .hash_type(ScriptHashType::Type.pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .output(CellOutput::new_builder().lock(lock(0)).build())
+            .output(
+                CellOutput::new_builder()
+                    .capacity(capacity_bytes!(100).pack())
+                    .lock(lock(1))
+                    .type_(Some(type_script.clone()).pack())
+                    .build(),
+            )
+            .outputs_data(vec![Bytes::new().pack(), Bytes::from("data").pack()])
+            .build()
+            .data();
+
+        let replaced = replace_output_locks(&tx, &[1], &lock(2)).unwrap();
+        let outputs = replaced.raw().outputs().into_iter().collect::<Vec<_>>();
+        assert_eq!(outputs[0].lock(), lock(0));
+        assert_eq!(outputs[1].lock(), lock(2));
+        assert_eq!(
+            Unpack::<u64>::unpack(&outputs[1].capacity()),
+            capacity_bytes!(100).as_u64()
+        );
+        assert_eq!(outputs[1].type_().to_opt(), Some(type_script));
+        assert_eq!(
+            replaced.raw().outputs_data().as_slice(),
+            tx.raw().outputs_data().as_slice()
+        );
+
+        let err = replace_output_locks(&tx, &[0, 2], &lock(2)).unwrap_err();
+        assert!(err.contains("out of range: 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_inputs_since() {
+        let epoch = |number, index, length| EpochNumberWithFraction::new(number, index, length);
+        let absolute = |metric, value| Since::new(metric, value, false).unwrap().0;
+        let sinces = vec![
+            0,
+            absolute(SinceMetric::BlockNumber, 100),
+            absolute(SinceMetric::EpochNumber, epoch(10, 0, 1).full_value()),
+            absolute(SinceMetric::EpochNumber, epoch(10, 1, 2).full_value()),
+            // Not checked without the chain context
+            absolute(SinceMetric::Timestamp, 1000),
+            Since::new(SinceMetric::BlockNumber, 1000, true).unwrap().0,
+            // Reserved metric bits
+            0x6000_0000_0000_0000,
+        ];
+        let mut mock_tx = MockTransaction::default();
+        mock_tx.tx = TransactionBuilder::default()
+            .inputs(sinces.into_iter().enumerate().map(|(index, since)| {
+                CellInput::new(OutPoint::new(h256!("0xff01").pack(), index as u32), since)
+            }))
+            .build()
+            .data();
+        let immature_indices = |current_block, current_epoch| {
+            check_inputs_since(&mock_tx, current_block, current_epoch)
+                .into_iter()
+                .map(|immature| immature["index"].as_u64().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // Only the invalid since is reported without the chain context
+        assert_eq!(immature_indices(None, None), vec![6]);
+        assert_eq!(
+            immature_indices(Some(99), Some(epoch(9, 0, 1))),
+            vec![1, 2, 3, 6]
+        );
+        assert_eq!(
+            immature_indices(Some(100), Some(epoch(10, 0, 1))),
+            vec![3, 6]
+        );
+        // Compared with the fraction
+        assert_eq!(
+            immature_indices(Some(100), Some(epoch(10, 1, 4))),
+            vec![3, 6]
+        );
+        assert_eq!(immature_indices(Some(100), Some(epoch(10, 2, 4))), vec![6]);
+        assert_eq!(immature_indices(Some(100), Some(epoch(11, 0, 1))), vec![6]);
+
+        let immature = check_inputs_since(&mock_tx, None, Some(epoch(10, 1, 4)));
+        assert_eq!(
+            immature[0]["reason"],
+            serde_json::json!("current epoch 10 (1/4) < since epoch 10 (1/2)")
+        );
+    }
+
+    #[test]
+    fn test_check_change_lock() {
+        let genesis_block: RpcBlockView = serde_json::from_str(GENESIS_JSON).unwrap();
+        let genesis_info = GenesisInfo::from_block(&genesis_block.into()).unwrap();
+        let controlled_lock_arg = H160([1u8; 20]);
+        let controlled_lock_args: HashSet<H160> =
+            vec![controlled_lock_arg.clone()].into_iter().collect();
+        let secp_lock = |lock_arg: &[u8]| {
+            Script::new_builder()
+                .code_hash(genesis_info.secp_type_hash().clone())
+                .hash_type(ScriptHashType::Type.pack())
+                .args(Bytes::from(lock_arg).pack())
+                .build()
+        };
+
+        assert!(check_change_lock(
+            &secp_lock(controlled_lock_arg.as_bytes()),
+            &genesis_info,
+            &controlled_lock_args
+        )
+        .is_ok());
+
+        let err = check_change_lock(&secp_lock(&[2u8; 20]), &genesis_info, &controlled_lock_args)
+            .unwrap_err();
+        assert!(err.contains("is not in key store"), "{}", err);
+        let not_secp_locks = vec![
+            secp_lock(&[1u8; 21]),
+            secp_lock(controlled_lock_arg.as_bytes())
+                .as_builder()
+                .hash_type(ScriptHashType::Data.pack())
+                .build(),
+            secp_lock(controlled_lock_arg.as_bytes())
+                .as_builder()
+                .code_hash(genesis_info.dao_type_hash().clone())
+                .build(),
+        ];
+        for lock in not_secp_locks {
+            let err = check_change_lock(&lock, &genesis_info, &controlled_lock_args).unwrap_err();
+            assert!(err.contains("is not a secp256k1 lock"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_expected_data_hashes() {
+        let script = |code: &str, hash_type: ScriptHashType| {
+            Script::new_builder()
+                .code_hash(CellOutput::calc_data_hash(code.as_bytes()))
+                .hash_type(hash_type.pack())
+                .build()
+        };
+        let mut mock_tx = MockTransaction::default();
+        let input = CellInput::new(OutPoint::new(h256!("0xff01").pack(), 0), 0);
+        mock_tx.mock_info.inputs.push(MockInput {
+            input: input.clone(),
+            output: CellOutput::new_builder()
+                .lock(script("lock", ScriptHashType::Data))
+                .type_(Some(script("by-type", ScriptHashType::Type)).pack())
+                .build(),
+            data: Bytes::default(),
+        });
+        // Provided by the mock info
+        mock_tx.mock_info.cell_deps.push(MockCellDep {
+            cell_dep: CellDep::default(),
+            output: CellOutput::default(),
+            data: Bytes::from("provided"),
+        });
+        let output = |type_script: Script| {
+            CellOutput::new_builder()
+                .type_(Some(type_script).pack())
+                .build()
+        };
+        mock_tx.tx = TransactionBuilder::default()
+            .input(input)
+            .output(output(script("provided", ScriptHashType::Data)))
+            .output(output(script("lock", ScriptHashType::Data)))
+            .output(output(script("type", ScriptHashType::Data)))
+            .build()
+            .data();
+
+        let data_hash =
+            |code: &str| -> H256 { CellOutput::calc_data_hash(code.as_bytes()).unpack() };
+        assert_eq!(
+            expected_data_hashes(&mock_tx),
+            vec![data_hash("lock"), data_hash("type")]
+        );
+    }
+
+    #[test]
+    fn test_fee_rate() {
+        assert_eq!(fee_rate(1000, 500), Ok(2000));
+        assert_eq!(
+            fee_rate(u64::max_value() / 1000, 1000),
+            Ok(u64::max_value() / 1000)
+        );
+        assert!(fee_rate(u64::max_value() / 1000 + 1, 1000).is_err());
+    }
+
+    fn header(number: u64, epoch: EpochNumberWithFraction) -> HeaderView {
+        HeaderBuilder::default()
+            .number(number.pack())
+            .epoch(epoch.full_value().pack())
+            .build()
+    }
+
+    #[test]
+    fn test_relative_since_immaturity() {
+        let epoch = EpochNumberWithFraction::new;
+        let relative = |metric, value| Since::new(metric, value, true).unwrap();
+        let created = header(100, epoch(10, 500, 1000));
+
+        let since = relative(SinceMetric::BlockNumber, 20);
+        assert!(
+            relative_since_immaturity(since, &created, &header(120, epoch(11, 0, 1000))).is_none()
+        );
+        let (_, spendable_at) =
+            relative_since_immaturity(since, &created, &header(119, epoch(11, 0, 1000))).unwrap();
+        assert_eq!(spendable_at, serde_json::json!({ "block": 120 }));
+
+        // Created at epoch 10 + 1/2, spendable at epoch 11 + 3/4, the epoch number alone is not enough
+        let since = relative(SinceMetric::EpochNumber, epoch(1, 1, 4).full_value());
+        let (_, spendable_at) =
+            relative_since_immaturity(since, &created, &header(300, epoch(11, 700, 1000))).unwrap();
+        assert_eq!(spendable_at, serde_json::json!({ "epoch": "11 (3/4)" }));
+        assert!(
+            relative_since_immaturity(since, &created, &header(300, epoch(11, 750, 1000)))
+                .is_none()
+        );
+
+        // Whole epochs, without length
+        let since = relative(SinceMetric::EpochNumber, 2);
+        let (_, spendable_at) =
+            relative_since_immaturity(since, &created, &header(500, epoch(12, 499, 1000))).unwrap();
+        assert_eq!(spendable_at, serde_json::json!({ "epoch": "12 (1/2)" }));
+        assert!(
+            relative_since_immaturity(since, &created, &header(500, epoch(12, 500, 1000)))
+                .is_none()
+        );
+
+        // Relative timestamp is not checked
+        let since = relative(SinceMetric::Timestamp, 1000);
+        assert!(relative_since_immaturity(since, &created, &created).is_none());
+    }
+
+    #[test]
+    fn test_epoch_from_rational() {
+        assert_eq!(format_epoch(epoch_from_rational(7 * 4 + 2, 4)), "7 (1/2)");
+        assert_eq!(format_epoch(epoch_from_rational(7 * 4, 4)), "7");
+        // Rounded up to fit the 16 bits length
+        assert_eq!(
+            format_epoch(epoch_from_rational(7 * 70_001 + 1, 70_001)),
+            "7 (1/65535)"
+        );
+        assert_eq!(
+            format_epoch(epoch_from_rational(7 * 70_001 + 70_000, 70_001)),
+            "8"
+        );
+    }
+
+    // NOTE: Should update when block structure changed
+    const GENESIS_JSON: &str = include_str!("../../../ckb-sdk/src/test-data/genesis_block.json");
+
+    /// A signed secp256k1 transfer with every cell it needs in the mock info
+    fn signed_mock_tx(genesis_block: &BlockView, genesis_info: &GenesisInfo) -> MockTransaction {
+        let privkey = secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, &privkey);
+        let lock_arg = H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).unwrap();
+        let lock_script = Script::new_builder()
+            .code_hash(genesis_info.secp_type_hash().clone())
+            .hash_type(ScriptHashType::Type.pack())
+            .args(Bytes::from(lock_arg.as_bytes()).pack())
+            .build();
+
+        let mut mock_tx = MockTransaction::default();
+        let genesis_cellbase = genesis_block.transactions()[0].clone();
+        let (dep_group_output, dep_group_data) =
+            genesis_block.transactions()[1].output_with_data(0).unwrap();
+        mock_tx.mock_info.cell_deps.push(MockCellDep {
+            cell_dep: genesis_info.secp_dep(),
+            output: dep_group_output,
+            data: dep_group_data,
+        });
+        for index in &[1, 3] {
+            let (output, data) = genesis_cellbase.output_with_data(*index).unwrap();
+            mock_tx.mock_info.cell_deps.push(MockCellDep {
+                cell_dep: CellDep::new_builder()
+                    .out_point(OutPoint::new(genesis_cellbase.hash(), *index as u32))
+                    .build(),
+                output,
+                data,
+            });
+        }
+        let input = CellInput::new(OutPoint::new(h256!("0xff01").pack(), 0), 0);
+        mock_tx.mock_info.inputs.push(MockInput {
+            input: input.clone(),
+            output: CellOutput::new_builder()
+                .capacity(capacity_bytes!(200).pack())
+                .lock(lock_script.clone())
+                .build(),
+            data: Bytes::default(),
+        });
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .input(input)
+            .output(
+                CellOutput::new_builder()
+                    .capacity(capacity_bytes!(120).pack())
+                    .lock(lock_script)
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build()
+            .data();
+
+        let signer = |_: &H160, tx_hash_hash: &H256| {
+            let message = secp256k1::Message::from_slice(tx_hash_hash.as_bytes()).unwrap();
+            let signature = SECP256K1.sign_recoverable(&message, &privkey);
+            let (recov_id, data) = signature.serialize_compact();
+            let mut signature_bytes = [0u8; 65];
+            signature_bytes[0..64].copy_from_slice(&data[0..64]);
+            signature_bytes[64] = recov_id.to_i32() as u8;
+            Ok(signature_bytes)
+        };
+        MockTransactionHelper::new(&mut mock_tx)
+            .complete_tx(None, genesis_info, signer, |out_point| {
+                OfflineLoader.get_live_cell(out_point)
+            })
+            .unwrap();
+        mock_tx
+    }
+
+    /// Run the mock-tx subcommand with a node which can't be reached, keeping the key store in dir
+    fn process_without_node(dir: &Path, args: &[&str]) -> Result<String, String> {
+        let keystore_dir = dir.join("keystore");
+        fs::create_dir_all(&keystore_dir).unwrap();
+        // Nothing listens on this port, any request to the node fails
+        let url = "http://127.0.0.1:1";
+        let mut rpc_client = HttpRpcClient::from_uri(url);
+        let mut key_store = KeyStore::from_dir(keystore_dir, ScryptType::default()).unwrap();
+        let matches = MockTxSubCommand::subcommand("mock-tx")
+            .get_matches_from(iter::once("mock-tx").chain(args.iter().cloned()));
+        MockTxSubCommand::new(
+            &mut rpc_client,
+            &mut key_store,
+            None,
+            None,
+            None,
+            url.to_owned(),
+        )
+        .process(&matches, OutputFormat::Json, false, false)
+    }
+
+    fn write_mock_tx(dir: &Path, name: &str, mock_tx: MockTransaction) -> String {
+        let path = dir.join(name);
+        let content = serde_json::to_string_pretty(&ReprMockTransaction::from(mock_tx)).unwrap();
+        fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ckb-cli-mock-tx-{}-{}", name, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_verify_cells_file_offline() {
+        let genesis_block: RpcBlockView = serde_json::from_str(GENESIS_JSON).unwrap();
+        let genesis_block: BlockView = genesis_block.into();
+        let genesis_info = GenesisInfo::from_block(&genesis_block).unwrap();
+        let mock_tx = signed_mock_tx(&genesis_block, &genesis_info);
+
+        let dir = test_dir("offline");
+        let genesis_path = dir.join("genesis.json");
+        fs::write(&genesis_path, GENESIS_JSON).unwrap();
+        let bundle_path = write_mock_tx(&dir, "bundle.json", mock_tx.clone());
+        let mut incomplete_tx = mock_tx;
+        incomplete_tx.mock_info.inputs.clear();
+        let incomplete_path = write_mock_tx(&dir, "incomplete.json", incomplete_tx);
+
+        let verify = |path: &str| {
+            process_without_node(
+                &dir,
+                &[
+                    "verify",
+                    "--tx-file",
+                    path,
+                    "--cells-file",
+                    path,
+                    "--genesis-file",
+                    genesis_path.to_str().unwrap(),
+                    "--no-complete",
+                ],
+            )
+        };
+        let result = verify(&bundle_path);
+        let incomplete_result = verify(&incomplete_path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let resp: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert!(resp["cycle"].as_u64().unwrap() > 0);
+        // The missing cell is reported instead of being loaded from the node
+        let err = incomplete_result.unwrap_err();
+        assert!(err.contains("not in bundle"), "{}", err);
+    }
+
+    #[test]
+    fn test_hash_without_node() {
+        let genesis_block: RpcBlockView = serde_json::from_str(GENESIS_JSON).unwrap();
+        let genesis_block: BlockView = genesis_block.into();
+        let genesis_info = GenesisInfo::from_block(&genesis_block).unwrap();
+        let mock_tx = signed_mock_tx(&genesis_block, &genesis_info);
+        let tx_hash = format!("{:#x}", mock_tx.core_transaction().hash());
+
+        let dir = test_dir("hash");
+        let tx_path = write_mock_tx(&dir, "tx.json", mock_tx);
+        let result = process_without_node(&dir, &["hash", "--tx-file", &tx_path]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.unwrap(), tx_hash);
+    }
+
+    #[test]
+    fn test_explain_error_without_node() {
+        let dir = test_dir("explain-error");
+        let result = process_without_node(&dir, &["explain-error", "--code", "-14"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            "script exit code -14: secp256k1 parse signature failed (system secp256k1 lock)"
+        );
+    }
+
+    fn genesis_mock_tx() -> MockTransaction {
+        let genesis_block: RpcBlockView = serde_json::from_str(GENESIS_JSON).unwrap();
+        let genesis_block: BlockView = genesis_block.into();
+        let genesis_info = GenesisInfo::from_block(&genesis_block).unwrap();
+        signed_mock_tx(&genesis_block, &genesis_info)
+    }
+
+    #[test]
+    fn test_graph_without_node() {
+        let mock_tx = genesis_mock_tx();
+        let dir = test_dir("graph");
+        let tx_path = write_mock_tx(&dir, "tx.json", mock_tx.clone());
+        let result = process_without_node(&dir, &["graph", "--tx-file", &tx_path]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.unwrap(), graph::to_dot(&mock_tx));
+    }
+
+    #[test]
+    fn test_molecule_without_node() {
+        let mock_tx = genesis_mock_tx();
+        let dir = test_dir("molecule");
+        let tx_path = write_mock_tx(&dir, "tx.json", mock_tx.clone());
+        let result = process_without_node(&dir, &["molecule", "--tx-file", &tx_path]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let resp: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(resp, molecule::layout(&mock_tx.tx));
+    }
+
+    #[test]
+    fn test_canonicalize_without_node() {
+        let mut mock_tx = genesis_mock_tx();
+        let last_dep = MockCellDep {
+            cell_dep: CellDep::new_builder()
+                .out_point(OutPoint::new(H256([0xff; 32]).pack(), 0))
+                .build(),
+            output: CellOutput::default(),
+            data: Bytes::new(),
+        };
+        mock_tx.mock_info.cell_deps.insert(0, last_dep.clone());
+        let dir = test_dir("canonicalize");
+        let tx_path = write_mock_tx(&dir, "tx.json", mock_tx);
+        let output_path = dir.join("canonical.json");
+        let result = process_without_node(
+            &dir,
+            &[
+                "canonicalize",
+                "--tx-file",
+                &tx_path,
+                "--output-file",
+                output_path.to_str().unwrap(),
+            ],
+        );
+        let canonical_tx = load_mock_tx_file(&output_path, false);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.unwrap(), "");
+        let cell_deps = canonical_tx.unwrap().mock_info.cell_deps;
+        assert_eq!(cell_deps.len(), 2);
+        assert_eq!(
+            cell_deps[1].cell_dep.as_slice(),
+            last_dep.cell_dep.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_witness_hash_without_node() {
+        let mock_tx = genesis_mock_tx();
+        let witness_hash = format!("{:#x}", mock_tx.core_transaction().witness_hash());
+        let dir = test_dir("witness-hash");
+        let tx_path = write_mock_tx(&dir, "tx.json", mock_tx);
+        let result = process_without_node(&dir, &["witness-hash", "--tx-file", &tx_path]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.unwrap(), witness_hash);
+    }
+
+    #[test]
+    fn test_since_without_node() {
+        let dir = test_dir("since");
+        let absolute = process_without_node(&dir, &["since", "--block", "100"]);
+        let relative = process_without_node(&dir, &["since", "--block", "100", "--relative"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(absolute.unwrap(), "0x64");
+        assert_eq!(relative.unwrap(), "0x8000000000000064");
+    }
+
+    #[test]
+    fn test_resolved_cells() {
+        let resolved_cells = ResolvedCells::default();
+        let out_point = OutPoint::new(h256!("0x1").pack(), 0);
+        let output = CellOutput::default();
+        let data = Bytes::from(vec![1, 2, 3]);
+        resolved_cells.record(out_point.clone(), &output, None);
+        assert!(resolved_cells.get(&out_point).is_none());
+        resolved_cells.record(out_point.clone(), &output, Some(&data));
+        // Loading the cell again without data keeps its data
+        resolved_cells.record(out_point.clone(), &output, None);
+        let (_, resolved_data) = resolved_cells.get(&out_point).unwrap();
+        assert_eq!(resolved_data, data);
+        assert!(resolved_cells
+            .get(&OutPoint::new(h256!("0x1").pack(), 1))
+            .is_none());
+    }
+}
@@ -0,0 +1,170 @@
+use ckb_types::{packed::Transaction, prelude::*};
+use serde_json::json;
+
+const NUMBER_SIZE: usize = 4;
+
+fn read_number(data: &[u8], pos: usize) -> usize {
+    let mut bytes = [0u8; NUMBER_SIZE];
+    bytes.copy_from_slice(&data[pos..pos + NUMBER_SIZE]);
+    u32::from_le_bytes(bytes) as usize
+}
+
+/// (offset, size) of the fields of a table or the items of a dynvec, both start with the
+/// total size and then the offsets of every part
+fn dynamic_parts(data: &[u8]) -> Vec<(usize, usize)> {
+    let total_size = read_number(data, 0);
+    if total_size == NUMBER_SIZE {
+        return Vec::new();
+    }
+    let count = read_number(data, NUMBER_SIZE) / NUMBER_SIZE - 1;
+    let offsets = (0..count)
+        .map(|idx| read_number(data, NUMBER_SIZE * (idx + 1)))
+        .chain(std::iter::once(total_size))
+        .collect::<Vec<_>>();
+    offsets
+        .windows(2)
+        .map(|window| (window[0], window[1] - window[0]))
+        .collect()
+}
+
+fn node(
+    name: String,
+    offset: usize,
+    size: usize,
+    parts: Vec<serde_json::Value>,
+) -> serde_json::Value {
+    if parts.is_empty() {
+        json!({ "name": name, "offset": offset, "size": size })
+    } else {
+        json!({ "name": name, "offset": offset, "size": size, "parts": parts })
+    }
+}
+
+/// Fixvec: item count then fixed size items
+fn fixvec_node(name: &str, data: &[u8], offset: usize, item_size: usize) -> serde_json::Value {
+    let count = read_number(data, offset);
+    let mut parts = vec![node(
+        "item_count".to_owned(),
+        offset,
+        NUMBER_SIZE,
+        Vec::new(),
+    )];
+    parts.extend((0..count).map(|idx| {
+        node(
+            format!("{}[{}]", name, idx),
+            offset + NUMBER_SIZE + idx * item_size,
+            item_size,
+            Vec::new(),
+        )
+    }));
+    node(
+        name.to_owned(),
+        offset,
+        NUMBER_SIZE + count * item_size,
+        parts,
+    )
+}
+
+/// Table or dynvec, `child` builds the node of each part from (name, offset, size)
+fn dynamic_node<F>(
+    name: &str,
+    data: &[u8],
+    offset: usize,
+    size: usize,
+    part_names: Option<&[&str]>,
+    mut child: F,
+) -> serde_json::Value
+where
+    F: FnMut(String, usize, usize) -> serde_json::Value,
+{
+    let dyn_parts = dynamic_parts(&data[offset..offset + size]);
+    let header_size = dyn_parts
+        .first()
+        .map(|(part_offset, _)| *part_offset)
+        .unwrap_or(NUMBER_SIZE);
+    let mut parts = vec![node("header".to_owned(), offset, header_size, Vec::new())];
+    for (idx, (part_offset, part_size)) in dyn_parts.into_iter().enumerate() {
+        let part_name = match part_names {
+            Some(names) => names[idx].to_owned(),
+            None => format!("{}[{}]", name, idx),
+        };
+        parts.push(child(part_name, offset + part_offset, part_size));
+    }
+    node(name.to_owned(), offset, size, parts)
+}
+
+/// Break the serialized transaction down to the molecule tables, vectors and their fields
+/// (offsets and sizes in bytes).
+pub fn layout(tx: &Transaction) -> serde_json::Value {
+    let data = tx.as_slice();
+    let leaf = |name: String, offset: usize, size: usize| node(name, offset, size, Vec::new());
+    dynamic_node(
+        "transaction",
+        data,
+        0,
+        data.len(),
+        Some(&["raw", "witnesses"]),
+        |name, offset, size| match name.as_str() {
+            "raw" => dynamic_node(
+                "raw",
+                data,
+                offset,
+                size,
+                Some(&[
+                    "version",
+                    "cell_deps",
+                    "header_deps",
+                    "inputs",
+                    "outputs",
+                    "outputs_data",
+                ]),
+                |name, offset, size| match name.as_str() {
+                    "cell_deps" => fixvec_node("cell_deps", data, offset, 37),
+                    "header_deps" => fixvec_node("header_deps", data, offset, 32),
+                    "inputs" => fixvec_node("inputs", data, offset, 44),
+                    "outputs" => {
+                        dynamic_node("outputs", data, offset, size, None, |name, offset, size| {
+                            dynamic_node(
+                                &name,
+                                data,
+                                offset,
+                                size,
+                                Some(&["capacity", "lock", "type"]),
+                                leaf,
+                            )
+                        })
+                    }
+                    "outputs_data" => dynamic_node("outputs_data", data, offset, size, None, leaf),
+                    _ => leaf(name, offset, size),
+                },
+            ),
+            _ => dynamic_node("witnesses", data, offset, size, None, leaf),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{bytes::Bytes, core::TransactionBuilder, packed::CellOutput};
+
+    #[test]
+    fn test_layout() {
+        let tx = TransactionBuilder::default()
+            .output(CellOutput::default())
+            .output_data(Bytes::from("abcd").pack())
+            .witness(Bytes::from(&[0u8; 65][..]).pack())
+            .build()
+            .data();
+        let layout = layout(&tx);
+        assert_eq!(layout["size"], json!(tx.as_slice().len()));
+        let raw = &layout["parts"][1];
+        assert_eq!(raw["name"], json!("raw"));
+        assert_eq!(raw["size"], json!(tx.raw().as_slice().len()));
+        let outputs_data = &raw["parts"][6];
+        assert_eq!(outputs_data["name"], json!("outputs_data"));
+        assert_eq!(outputs_data["parts"][1]["size"], json!(4 + 4));
+        let witnesses = &layout["parts"][2];
+        assert_eq!(witnesses["parts"][1]["size"], json!(4 + 65));
+    }
+}
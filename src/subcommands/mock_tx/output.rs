@@ -0,0 +1,124 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use flate2::{write::GzEncoder, Compression};
+use url::Url;
+
+use super::is_gzip_path;
+use crate::utils::arg_parser::{ArgParser, FilePathParser};
+
+/// Timeout (seconds) of posting to an http output, an unresponsive server fails the command
+/// instead of hanging it
+const HTTP_TIMEOUT: u64 = 30;
+
+/// Where the rendered transaction is written to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputTarget {
+    Stdout,
+    /// gzip compressed if the path ends with .gz
+    File(PathBuf),
+    /// POST the content to the url
+    Http(Url),
+    Clipboard,
+}
+
+pub struct OutputTargetParser;
+
+impl ArgParser<OutputTarget> for OutputTargetParser {
+    fn parse(&self, input: &str) -> Result<OutputTarget, String> {
+        if input == "clipboard:" {
+            Ok(OutputTarget::Clipboard)
+        } else if input.starts_with("http://") || input.starts_with("https://") {
+            Url::parse(input)
+                .map(OutputTarget::Http)
+                .map_err(|err| err.to_string())
+        } else if input.starts_with("file://") {
+            FilePathParser::new(false)
+                .parse(&input["file://".len()..])
+                .map(OutputTarget::File)
+        } else {
+            FilePathParser::new(false)
+                .parse(input)
+                .map(OutputTarget::File)
+        }
+    }
+}
+
+impl OutputTarget {
+    /// Content written to a terminal can be colored
+    pub fn is_stdout(&self) -> bool {
+        self == &OutputTarget::Stdout
+    }
+
+    pub fn write(&self, content: &str) -> Result<(), String> {
+        match self {
+            OutputTarget::Stdout => {
+                println!("{}", content);
+                Ok(())
+            }
+            OutputTarget::File(path) => {
+                let out_file = fs::File::create(path).map_err(|err| err.to_string())?;
+                if is_gzip_path(path) {
+                    let mut encoder = GzEncoder::new(out_file, Compression::default());
+                    encoder
+                        .write_all(content.as_bytes())
+                        .map_err(|err| err.to_string())?;
+                    encoder.finish().map_err(|err| err.to_string())?;
+                } else {
+                    let mut out_file = out_file;
+                    out_file
+                        .write_all(content.as_bytes())
+                        .map_err(|err| err.to_string())?;
+                }
+                Ok(())
+            }
+            OutputTarget::Http(url) => {
+                let resp = reqwest::Client::builder()
+                    .timeout(Duration::from_secs(HTTP_TIMEOUT))
+                    .build()
+                    .map_err(|err| err.to_string())?
+                    .post(url.as_str())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(content.to_owned())
+                    .send()
+                    .map_err(|err| format!("POST to {} error: {}", url, err))?;
+                if !resp.status().is_success() {
+                    return Err(format!("POST to {} failed: {}", url, resp.status()));
+                }
+                Ok(())
+            }
+            OutputTarget::Clipboard => copy_to_clipboard(content),
+        }
+    }
+}
+
+fn copy_to_clipboard(content: &str) -> Result<(), String> {
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("pbcopy", &[])
+    } else if cfg!(windows) {
+        ("clip", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Run clipboard command {} error: {}", program, err))?;
+    child
+        .stdin
+        .take()
+        .expect("clipboard stdin is piped")
+        .write_all(content.as_bytes())
+        .map_err(|err| format!("Write to clipboard command error: {}", err))?;
+    let status = child
+        .wait()
+        .map_err(|err| format!("Run clipboard command {} error: {}", program, err))?;
+    if !status.success() {
+        return Err(format!("Clipboard command {} failed: {}", program, status));
+    }
+    Ok(())
+}
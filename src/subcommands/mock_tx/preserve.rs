@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TScalarStyle};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PathKey {
+    Key(String),
+    Index(usize),
+}
+
+type Path = Vec<PathKey>;
+
+fn path_desc(path: &[PathKey]) -> String {
+    let mut desc = String::new();
+    for key in path {
+        match key {
+            PathKey::Key(key) => {
+                if !desc.is_empty() {
+                    desc.push('.');
+                }
+                desc.push_str(key);
+            }
+            PathKey::Index(index) => desc.push_str(&format!("[{}]", index)),
+        }
+    }
+    desc
+}
+
+/// Apply the changes from `base` to `new` (both rendered from the mock transaction) to the
+/// YAML `source` in place, everything else in the source (comments, order, quoting) is kept.
+/// Only changed scalars can be patched, an error is returned if the structure changed (e.g. an
+/// item added or removed) or a changed scalar can not be located in the source.
+pub fn patch_yaml(source: &str, base: &Value, new: &Value) -> Result<String, String> {
+    let mut changes = Vec::new();
+    diff_values(base, new, &mut Vec::new(), &mut changes)?;
+    if changes.is_empty() {
+        return Ok(source.to_owned());
+    }
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut collector = SpanCollector {
+        chars: &chars,
+        stack: Vec::new(),
+        spans: HashMap::new(),
+    };
+    Parser::new(source.chars())
+        .load(&mut collector, false)
+        .map_err(|err| format!("parse yaml error: {}", err))?;
+    let mut replacements = Vec::new();
+    for (path, value) in changes {
+        match collector.spans.get(&path) {
+            Some(Some(span)) => {
+                let text = serde_json::to_string(&value).map_err(|err| err.to_string())?;
+                replacements.push((*span, text));
+            }
+            Some(None) => {
+                return Err(format!(
+                    "{} is a multi-line or block scalar",
+                    path_desc(&path)
+                ))
+            }
+            None => return Err(format!("{} not found in the file", path_desc(&path))),
+        }
+    }
+    replacements.sort_by_key(|((start, _), _)| *start);
+    let mut output = String::new();
+    let mut last = 0;
+    for ((start, end), text) in replacements {
+        output.extend(&chars[last..start]);
+        output.push_str(&text);
+        last = end;
+    }
+    output.extend(&chars[last..]);
+    Ok(output)
+}
+
+/// Collect the scalars changed from `base` to `new`, the structure must be the same
+fn diff_values(
+    base: &Value,
+    new: &Value,
+    path: &mut Path,
+    changes: &mut Vec<(Path, Value)>,
+) -> Result<(), String> {
+    match (base, new) {
+        (Value::Object(base_map), Value::Object(new_map)) => {
+            if base_map.len() != new_map.len()
+                || base_map.keys().any(|key| !new_map.contains_key(key))
+            {
+                return Err(format!("fields of {} changed", path_desc(path)));
+            }
+            for (key, base_value) in base_map {
+                path.push(PathKey::Key(key.clone()));
+                diff_values(base_value, &new_map[key], path, changes)?;
+                path.pop();
+            }
+        }
+        (Value::Array(base_items), Value::Array(new_items)) => {
+            if base_items.len() != new_items.len() {
+                return Err(format!("items of {} added or removed", path_desc(path)));
+            }
+            for (index, (base_item, new_item)) in base_items.iter().zip(new_items).enumerate() {
+                path.push(PathKey::Index(index));
+                diff_values(base_item, new_item, path, changes)?;
+                path.pop();
+            }
+        }
+        (Value::Object(_), _)
+        | (Value::Array(_), _)
+        | (_, Value::Object(_))
+        | (_, Value::Array(_)) => {
+            return Err(format!("type of {} changed", path_desc(path)));
+        }
+        _ => {
+            if base != new {
+                changes.push((path.clone(), new.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+enum Container {
+    /// The key of the value being parsed, None when expecting a key
+    Mapping(Option<String>),
+    /// Index of the item being parsed
+    Sequence(usize),
+}
+
+/// Record the span (char offsets) of every scalar value by its path, None if the span of the
+/// scalar can not be patched safely
+struct SpanCollector<'a> {
+    chars: &'a [char],
+    /// Containers being parsed and whether they are flow style (`[..]`, `{..}`)
+    stack: Vec<(Container, bool)>,
+    spans: HashMap<Path, Option<(usize, usize)>>,
+}
+
+impl<'a> SpanCollector<'a> {
+    fn current_path(&self) -> Path {
+        self.stack
+            .iter()
+            .filter_map(|(container, _)| match container {
+                Container::Mapping(key) => key.clone().map(PathKey::Key),
+                Container::Sequence(index) => Some(PathKey::Index(*index)),
+            })
+            .collect()
+    }
+
+    /// Whether the next scalar is a mapping key, record it if so
+    fn take_key(&mut self, scalar: &str) -> bool {
+        match self.stack.last_mut() {
+            Some((Container::Mapping(key), _)) if key.is_none() => {
+                *key = Some(scalar.to_owned());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// A value is finished, move to the next key or item
+    fn advance(&mut self) {
+        match self.stack.last_mut() {
+            Some((Container::Mapping(key), _)) => *key = None,
+            Some((Container::Sequence(index), _)) => *index += 1,
+            None => {}
+        }
+    }
+
+    fn in_flow(&self) -> bool {
+        self.stack.last().map(|(_, flow)| *flow).unwrap_or(false)
+    }
+
+    fn scalar_span(
+        &self,
+        value: &str,
+        style: TScalarStyle,
+        start: usize,
+    ) -> Option<(usize, usize)> {
+        let chars = self.chars;
+        match style {
+            TScalarStyle::Plain => {
+                let in_flow = self.in_flow();
+                let mut end = start;
+                while end < chars.len() {
+                    let c = chars[end];
+                    if c == '\n'
+                        || c == '\r'
+                        || (c == '#' && end > start && chars[end - 1].is_whitespace())
+                        || (in_flow && (c == ',' || c == ']' || c == '}'))
+                    {
+                        break;
+                    }
+                    end += 1;
+                }
+                while end > start && chars[end - 1].is_whitespace() {
+                    end -= 1;
+                }
+                let raw = chars[start..end].iter().collect::<String>();
+                if raw == value {
+                    Some((start, end))
+                } else {
+                    None
+                }
+            }
+            TScalarStyle::SingleQuoted => {
+                let mut end = start + 1;
+                while end < chars.len() {
+                    if chars[end] == '\'' {
+                        if chars.get(end + 1) == Some(&'\'') {
+                            end += 2;
+                            continue;
+                        }
+                        return Some((start, end + 1));
+                    }
+                    end += 1;
+                }
+                None
+            }
+            TScalarStyle::DoubleQuoted => {
+                let mut end = start + 1;
+                while end < chars.len() {
+                    match chars[end] {
+                        '\\' => end += 2,
+                        '"' => return Some((start, end + 1)),
+                        _ => end += 1,
+                    }
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> MarkedEventReceiver for SpanCollector<'a> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, style, _, _) => {
+                if self.take_key(&value) {
+                    return;
+                }
+                let span = self.scalar_span(&value, style, mark.index());
+                self.spans.insert(self.current_path(), span);
+                self.advance();
+            }
+            Event::MappingStart(_) => {
+                let flow = self.chars.get(mark.index()) == Some(&'{');
+                self.stack.push((Container::Mapping(None), flow));
+            }
+            Event::SequenceStart(_) => {
+                let flow = self.chars.get(mark.index()) == Some(&'[');
+                self.stack.push((Container::Sequence(0), flow));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.advance();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_yaml() {
+        let source = concat!(
+            "# my transaction\n",
+            "tx:\n",
+            "  version: '0x0' # keep me\n",
+            "  witnesses:\n",
+            "    - \"0x00\"\n",
+            "    - 0x01\n",
+            "  deps: [\"0xaa\", 0xbb]\n",
+        );
+        let base: Value = serde_yaml::from_str(source).unwrap();
+        let mut new = base.clone();
+        new["tx"]["version"] = Value::from("0x1");
+        new["tx"]["witnesses"][1] = Value::from("0x02");
+        new["tx"]["deps"][1] = Value::from("0xcc");
+        let patched = patch_yaml(source, &base, &new).unwrap();
+        assert_eq!(
+            patched,
+            concat!(
+                "# my transaction\n",
+                "tx:\n",
+                "  version: \"0x1\" # keep me\n",
+                "  witnesses:\n",
+                "    - \"0x00\"\n",
+                "    - \"0x02\"\n",
+                "  deps: [\"0xaa\", \"0xcc\"]\n",
+            )
+        );
+        let reparsed: Value = serde_yaml::from_str(&patched).unwrap();
+        assert_eq!(reparsed, new);
+
+        let mut added = base.clone();
+        added["tx"]["witnesses"]
+            .as_array_mut()
+            .unwrap()
+            .push(Value::from("0x03"));
+        assert!(patch_yaml(source, &base, &added).is_err());
+    }
+}
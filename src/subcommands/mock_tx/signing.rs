@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+
+use ckb_hash::blake2b_256;
+use ckb_sdk::{GenesisInfo, MockTransaction, MockTransactionHelper};
+use ckb_types::{
+    bytes::Bytes,
+    packed::{CellOutput, OutPoint},
+    prelude::*,
+    H160, H256,
+};
+use faster_hex::hex_string;
+use serde_json::json;
+
+use super::SECP_SIGNATURE_SIZE;
+use crate::utils::arg_parser::{ArgParser, FixedHashParser, HexParser};
+
+/// Secp256k1 lock args of the inputs and the input indices of each
+fn secp_groups<C>(
+    mock_tx: &MockTransaction,
+    genesis_info: &GenesisInfo,
+    mut live_cell_getter: C,
+) -> Result<Vec<(H160, Vec<usize>)>, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let mut groups: Vec<(H160, Vec<usize>)> = Vec::new();
+    for (idx, input) in mock_tx.tx.raw().inputs().into_iter().enumerate() {
+        let (output, _) = mock_tx
+            .get_input_cell(&input, &mut live_cell_getter)?
+            .ok_or_else(|| format!("input cell not found: {}", input))?;
+        let lock = output.lock();
+        if &lock.code_hash() != genesis_info.secp_type_hash() {
+            continue;
+        }
+        let lock_arg = match H160::from_slice(&lock.args().raw_data()) {
+            Ok(lock_arg) => lock_arg,
+            Err(_) => continue,
+        };
+        match groups.iter_mut().find(|(arg, _)| arg == &lock_arg) {
+            Some((_, indices)) => indices.push(idx),
+            None => groups.push((lock_arg, vec![idx])),
+        }
+    }
+    Ok(groups)
+}
+
+/// The message an offline signer signs (same for every secp256k1 input) and the inputs of
+/// each lock arg, the signatures are imported back by `import_signatures`.
+pub fn signing_request<C>(
+    mock_tx: &MockTransaction,
+    genesis_info: &GenesisInfo,
+    live_cell_getter: C,
+) -> Result<serde_json::Value, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx_hash = mock_tx.core_transaction().hash();
+    let message = blake2b_256(tx_hash.as_slice());
+    let groups = secp_groups(mock_tx, genesis_info, live_cell_getter)?
+        .into_iter()
+        .map(|(lock_arg, indices)| {
+            json!({
+                "lock_arg": format!("{:#x}", lock_arg),
+                "inputs": indices,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({
+        "tx_hash": format!("{:#x}", tx_hash),
+        "message": format!("0x{}", hex_string(&message).expect("hex")),
+        "groups": groups,
+    }))
+}
+
+/// The message hash signed for each secp256k1 lock arg, blake2b of the transaction hash in
+/// this chain version, so it is the same for every group. With `dump_preimage` the bytes
+/// hashed into the message (the transaction hash, no witness is included) are added.
+pub fn sign_hashes<C>(
+    mock_tx: &MockTransaction,
+    genesis_info: &GenesisInfo,
+    dump_preimage: bool,
+    live_cell_getter: C,
+) -> Result<serde_json::Value, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let tx_hash = mock_tx.core_transaction().hash();
+    let preimage = tx_hash.as_slice();
+    let message = format!("0x{}", hex_string(&blake2b_256(preimage)).expect("hex"));
+    let groups = secp_groups(mock_tx, genesis_info, live_cell_getter)?
+        .into_iter()
+        .map(|(lock_arg, indices)| {
+            let mut group = json!({
+                "lock_arg": format!("{:#x}", lock_arg),
+                "inputs": indices,
+                "message": message,
+            });
+            if dump_preimage {
+                group["preimage"] = json!(format!("0x{}", hex_string(preimage).expect("hex")));
+            }
+            group
+        })
+        .collect::<Vec<_>>();
+    Ok(json!(groups))
+}
+
+/// Parse the signatures file, a json object of lock arg to signature (hex)
+pub fn parse_signatures(content: &str) -> Result<HashMap<H160, [u8; 65]>, String> {
+    let raw: HashMap<String, String> = serde_json::from_str(content)
+        .map_err(|err| format!("Parse signatures file error: {}", err))?;
+    let mut signatures = HashMap::new();
+    for (lock_arg, signature) in raw {
+        let lock_arg: H160 = FixedHashParser::<H160>::default().parse(&lock_arg)?;
+        let signature_bytes = HexParser.parse(&signature)?;
+        if signature_bytes.len() != SECP_SIGNATURE_SIZE {
+            return Err(format!(
+                "Invalid signature length of lock_arg {:#x}: {}, expected: {}",
+                lock_arg,
+                signature_bytes.len(),
+                SECP_SIGNATURE_SIZE
+            ));
+        }
+        let mut data = [0u8; 65];
+        data.copy_from_slice(&signature_bytes);
+        signatures.insert(lock_arg, data);
+    }
+    Ok(signatures)
+}
+
+/// Put the signatures into the witnesses of the inputs locked by their lock args, return how
+/// many inputs are signed
+pub fn import_signatures<C>(
+    mock_tx: &mut MockTransaction,
+    genesis_info: &GenesisInfo,
+    signatures: &HashMap<H160, [u8; 65]>,
+    mut live_cell_getter: C,
+) -> Result<usize, String>
+where
+    C: FnMut(OutPoint) -> Result<Option<(CellOutput, Bytes)>, String>,
+{
+    let sign_indices: HashSet<usize> = secp_groups(mock_tx, genesis_info, &mut live_cell_getter)?
+        .into_iter()
+        .filter(|(lock_arg, _)| signatures.contains_key(lock_arg))
+        .flat_map(|(_, indices)| indices)
+        .collect();
+    let signer = |lock_arg: &H160, _: &H256| -> Result<[u8; 65], String> {
+        signatures
+            .get(lock_arg)
+            .cloned()
+            .ok_or_else(|| format!("No signature of lock_arg {:#x}", lock_arg))
+    };
+    let mut helper = MockTransactionHelper::new(mock_tx);
+    helper.fill_witnesses_opt(genesis_info, signer, live_cell_getter, Some(&sign_indices))?;
+    Ok(sign_indices.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signatures() {
+        let lock_arg = format!("0x{}", "11".repeat(20));
+        let content = format!(r#"{{"{}": "0x{}"}}"#, lock_arg, "22".repeat(65));
+        let signatures = parse_signatures(&content).unwrap();
+        let lock_arg: H160 = FixedHashParser::<H160>::default().parse(&lock_arg).unwrap();
+        assert_eq!(&signatures[&lock_arg][..], &[0x22u8; 65][..]);
+
+        let content = format!(r#"{{"{}": "0x2222"}}"#, "0x".to_owned() + &"11".repeat(20));
+        assert!(parse_signatures(&content).is_err());
+    }
+}
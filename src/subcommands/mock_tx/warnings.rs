@@ -0,0 +1,26 @@
+use colored::Colorize;
+
+/// All warnings of the mock-tx flow are raised through here, in strict mode a warning is an error
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Warnings {
+    strict: bool,
+}
+
+impl Warnings {
+    pub fn new(strict: bool) -> Warnings {
+        Warnings { strict }
+    }
+
+    /// Print the warning, or return it as an error in strict mode to stop the command
+    pub fn raise<S: Into<String>>(&self, warning: S) -> Result<(), String> {
+        let warning = warning.into();
+        if self.strict {
+            return Err(format!(
+                "{} (warning treated as error by --strict)",
+                warning
+            ));
+        }
+        eprintln!("[{}]: {}", "warning".yellow().bold(), warning);
+        Ok(())
+    }
+}
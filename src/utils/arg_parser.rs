@@ -7,7 +7,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use ckb_sdk::{wallet::MasterPrivKey, Address, NetworkType, OldAddress, ONE_CKB};
-use ckb_types::{packed::OutPoint, prelude::*, H160, H256};
+use ckb_types::{core::EpochNumberWithFraction, packed::OutPoint, prelude::*, H160, H256};
 use clap::ArgMatches;
 use faster_hex::hex_decode;
 use url::Url;
@@ -152,6 +152,22 @@ impl ArgParser<Vec<u8>> for HexParser {
     }
 }
 
+/// Standard or URL-safe base64, the padding is optional
+pub struct Base64Parser;
+
+impl ArgParser<Vec<u8>> for Base64Parser {
+    fn parse(&self, input: &str) -> Result<Vec<u8>, String> {
+        let input = input.trim_end_matches('=');
+        let config = if input.contains('+') || input.contains('/') {
+            base64::STANDARD_NO_PAD
+        } else {
+            base64::URL_SAFE_NO_PAD
+        };
+        base64::decode_config(input, config)
+            .map_err(|err| format!("parse base64 string failed: {}", err))
+    }
+}
+
 #[derive(Default)]
 pub struct FixedHashParser<T> {
     _h: PhantomData<T>,
@@ -216,11 +232,13 @@ pub struct DirPathParser {
     path_parser: PathParser,
 }
 
-// impl DirPathParser {
-//     pub fn new(should_exists: bool) -> DirPathParser {
-//         DirPathParser { path_parser: PathParser { should_exists } }
-//     }
-// }
+impl DirPathParser {
+    pub fn new(should_exists: bool) -> DirPathParser {
+        DirPathParser {
+            path_parser: PathParser { should_exists },
+        }
+    }
+}
 
 impl ArgParser<PathBuf> for DirPathParser {
     fn parse(&self, input: &str) -> Result<PathBuf, String> {
@@ -333,7 +351,6 @@ impl ArgParser<u64> for CapacityParser {
     }
 }
 
-#[allow(dead_code)]
 pub struct OutPointParser;
 
 impl ArgParser<OutPoint> for OutPointParser {
@@ -351,6 +368,44 @@ impl ArgParser<OutPoint> for OutPointParser {
     }
 }
 
+/// Epoch with an optional fraction: {number}[.{index}/{length}], e.g. 10 or 10.1/2
+pub struct EpochParser;
+
+impl ArgParser<EpochNumberWithFraction> for EpochParser {
+    fn parse(&self, input: &str) -> Result<EpochNumberWithFraction, String> {
+        let (number_str, fraction) = match input.find('.') {
+            Some(pos) => (&input[..pos], Some(&input[pos + 1..])),
+            None => (input, None),
+        };
+        let number = FromStrParser::<u64>::default().parse(number_str)?;
+        if number > 0xff_ffff {
+            return Err(format!("epoch number too large: {}", number));
+        }
+        let (index, length) = match fraction {
+            Some(fraction) => {
+                let parts = fraction.split('/').collect::<Vec<_>>();
+                if parts.len() != 2 {
+                    return Err(format!(
+                        "Invalid epoch: {}, format: {{number}}[.{{index}}/{{length}}]",
+                        input
+                    ));
+                }
+                let index = FromStrParser::<u16>::default().parse(parts[0])?;
+                let length = FromStrParser::<u16>::default().parse(parts[1])?;
+                if index >= length {
+                    return Err(format!(
+                        "epoch index {} must be less than length {}",
+                        index, length
+                    ));
+                }
+                (u64::from(index), u64::from(length))
+            }
+            None => (0, 1),
+        };
+        Ok(EpochNumberWithFraction::new(number, index, length))
+    }
+}
+
 pub struct DurationParser;
 
 impl ArgParser<Duration> for DurationParser {
@@ -406,6 +461,31 @@ mod tests {
         assert!(HexParser.parse("abcdefghi").is_err());
     }
 
+    #[test]
+    fn test_base64() {
+        assert_eq!(Base64Parser.parse("+/8="), Ok(vec![0xfb, 0xff]));
+        assert_eq!(Base64Parser.parse("-_8"), Ok(vec![0xfb, 0xff]));
+        assert_eq!(Base64Parser.parse("YWJj"), Ok(b"abc".to_vec()));
+        assert!(Base64Parser.parse("YW?j").is_err());
+    }
+
+    #[test]
+    fn test_epoch() {
+        let full_value = |input| EpochParser.parse(input).map(|epoch| epoch.full_value());
+        assert_eq!(
+            full_value("10"),
+            Ok(EpochNumberWithFraction::new(10, 0, 1).full_value())
+        );
+        assert_eq!(
+            full_value("10.1/2"),
+            Ok(EpochNumberWithFraction::new(10, 1, 2).full_value())
+        );
+        assert!(EpochParser.parse("10.2/2").is_err());
+        assert!(EpochParser.parse("10.1").is_err());
+        assert!(EpochParser.parse("10.1/70000").is_err());
+        assert!(EpochParser.parse("16777216").is_err());
+    }
+
     #[test]
     fn test_fixed_hash() {
         assert_eq!(
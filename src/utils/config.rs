@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use ansi_term::Colour::Yellow;
+use ckb_sdk::NetworkType;
 use ckb_util::RwLock;
 use regex::{Captures, Regex};
 
@@ -21,6 +22,8 @@ pub struct GlobalConfig {
     path: PathBuf,
     completion_style: bool,
     edit_style: bool,
+    fee_rate: Option<u64>,
+    network: Option<NetworkType>,
     env_variable: HashMap<String, serde_json::Value>,
     index_state: Arc<RwLock<IndexThreadState>>,
 }
@@ -35,6 +38,8 @@ impl GlobalConfig {
             path: env::current_dir().unwrap(),
             completion_style: true,
             edit_style: true,
+            fee_rate: None,
+            network: None,
             env_variable: HashMap::new(),
             index_state,
         }
@@ -154,6 +159,14 @@ impl GlobalConfig {
         self.edit_style = value;
     }
 
+    pub fn set_fee_rate(&mut self, value: Option<u64>) {
+        self.fee_rate = value;
+    }
+
+    pub fn set_network(&mut self, value: Option<NetworkType>) {
+        self.network = value;
+    }
+
     pub fn color(&self) -> bool {
         self.color
     }
@@ -174,6 +187,14 @@ impl GlobalConfig {
         self.edit_style
     }
 
+    pub fn fee_rate(&self) -> Option<u64> {
+        self.fee_rate
+    }
+
+    pub fn network(&self) -> Option<NetworkType> {
+        self.network
+    }
+
     pub fn print(&self) {
         let path = self.path.to_string_lossy();
         let color = self.color.to_string();
@@ -185,6 +206,11 @@ impl GlobalConfig {
             "Circular"
         };
         let edit_style = if self.edit_style { "Emacs" } else { "Vi" };
+        let fee_rate = self
+            .fee_rate
+            .map(|fee_rate| format!("{} shannons/KB", fee_rate))
+            .unwrap_or_else(|| "none".to_string());
+        let network = self.network.map(NetworkType::to_str).unwrap_or("none");
         let index_state = self.index_state.read().to_string();
         let version = crate::get_version();
         let version_long = version.long();
@@ -197,6 +223,8 @@ impl GlobalConfig {
             ("output format", output_format.as_str()),
             ("completion style", completion_style),
             ("edit style", edit_style),
+            ("fee rate", fee_rate.as_str()),
+            ("network", network),
             ("index db state", index_state.as_str()),
         ];
 
@@ -1,11 +1,16 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use ckb_jsonrpc_types::{AlertMessage, BlockNumber};
+use ckb_hash::blake2b_256;
+use ckb_jsonrpc_types::{AlertMessage, BlockNumber, HeaderView};
 use ckb_sdk::{
     wallet::{KeyStore, ScryptType},
-    Address, GenesisInfo, HttpRpcClient,
+    Address, GenesisInfo, HttpRpcClient, SECP256K1,
 };
 use ckb_types::{core::BlockView, H160, H256};
 use clap::ArgMatches;
@@ -28,6 +33,39 @@ pub fn read_password(repeat: bool, prompt: Option<&str>) -> Result<String, Strin
     Ok(pass)
 }
 
+/// Service name of the key store passwords saved in the OS keyring, the account is the lock arg
+/// (hex, without 0x)
+pub const KEYRING_SERVICE: &str = "ckb-cli";
+
+/// Look up the key store password of `lock_arg` in the OS keyring (`security` on macOS,
+/// `secret-tool` of libsecret elsewhere), None if there is no keyring tool or no entry
+pub fn read_keyring_password(lock_arg: &H160) -> Option<String> {
+    let account = format!("{:x}", lock_arg);
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(&["find-generic-password", "-s", KEYRING_SERVICE, "-a"])
+            .arg(&account)
+            .arg("-w")
+            .output()
+    } else {
+        Command::new("secret-tool")
+            .args(&["lookup", "service", KEYRING_SERVICE, "account"])
+            .arg(&account)
+            .output()
+    }
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let password = String::from_utf8(output.stdout).ok()?;
+    let password = password.trim_end_matches(|c| c == '\n' || c == '\r');
+    if password.is_empty() {
+        None
+    } else {
+        Some(password.to_owned())
+    }
+}
+
 pub fn get_key_store(ckb_cli_dir: &PathBuf) -> Result<KeyStore, String> {
     let mut keystore_dir = ckb_cli_dir.clone();
     keystore_dir.push("keystore");
@@ -51,15 +89,99 @@ pub fn get_address(m: &ArgMatches) -> Result<Address, String> {
     Ok(address)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PubkeyFormat {
+    Compressed,
+    Uncompressed,
+}
+
+impl FromStr for PubkeyFormat {
+    type Err = String;
+    fn from_str(input: &str) -> Result<PubkeyFormat, String> {
+        match input {
+            "compressed" => Ok(PubkeyFormat::Compressed),
+            "uncompressed" => Ok(PubkeyFormat::Uncompressed),
+            _ => Err(format!("Invalid public key format: {}", input)),
+        }
+    }
+}
+
+/// The lock arg (blake160) of the public key serialized in `format`
+pub fn pubkey_lock_arg(pubkey: &secp256k1::PublicKey, format: PubkeyFormat) -> H160 {
+    let hash = match format {
+        PubkeyFormat::Compressed => blake2b_256(&pubkey.serialize()[..]),
+        PubkeyFormat::Uncompressed => blake2b_256(&pubkey.serialize_uncompressed()[..]),
+    };
+    H160::from_slice(&hash[0..20]).expect("Generate hash(H160) from pubkey failed")
+}
+
+/// Signer checks the lock arg derived (by `pubkey_format`) from the signature matches the
+/// input's lock arg. Accounts of key store are identified by compressed public key, so accounts
+/// for uncompressed lock args are searched from the unlocked accounts. With `use_keyring` the
+/// password is taken from the OS keyring, and prompted for if the keyring has no entry.
 pub fn get_singer(
-    key_store: KeyStore,
+    mut key_store: KeyStore,
+    pubkey_format: PubkeyFormat,
+    use_keyring: bool,
 ) -> impl Fn(&H160, &H256) -> Result<[u8; 65], String> + 'static {
+    let mut accounts: HashMap<H160, H160> = HashMap::default();
+    if pubkey_format == PubkeyFormat::Uncompressed {
+        let account_ids = key_store.get_accounts().keys().cloned().collect::<Vec<_>>();
+        for account_id in account_ids {
+            if key_store.get_lock_timeout(&account_id).is_none() {
+                continue;
+            }
+            if let Ok(xpub) = key_store.extended_pubkey(&account_id, None) {
+                accounts.insert(pubkey_lock_arg(&xpub.public_key, pubkey_format), account_id);
+            }
+        }
+    }
+    let key_store = RefCell::new(key_store);
     move |lock_arg: &H160, tx_hash_hash: &H256| {
-        let prompt = format!("Password for [{:x}]", lock_arg);
-        let password = read_password(false, Some(prompt.as_str()))?;
-        let signature = key_store
-            .sign_recoverable_with_password(lock_arg, tx_hash_hash, password.as_bytes())
-            .map_err(|err| err.to_string())?;
+        let signature = match pubkey_format {
+            PubkeyFormat::Compressed => {
+                let keyring_password = if use_keyring {
+                    read_keyring_password(lock_arg)
+                } else {
+                    None
+                };
+                let password = match keyring_password {
+                    Some(password) => password,
+                    None => {
+                        let prompt = format!("Password for [{:x}]", lock_arg);
+                        read_password(false, Some(prompt.as_str()))?
+                    }
+                };
+                key_store
+                    .borrow()
+                    .sign_recoverable_with_password(lock_arg, tx_hash_hash, password.as_bytes())
+                    .map_err(|err| err.to_string())?
+            }
+            PubkeyFormat::Uncompressed => {
+                let account_id = accounts.get(lock_arg).ok_or_else(|| {
+                    format!(
+                        "No unlocked account matches lock arg {:x} (uncompressed public key), please unlock the account first",
+                        lock_arg
+                    )
+                })?;
+                key_store
+                    .borrow_mut()
+                    .sign_recoverable(account_id, tx_hash_hash)
+                    .map_err(|err| err.to_string())?
+            }
+        };
+        let message = secp256k1::Message::from_slice(tx_hash_hash.as_bytes())
+            .expect("Convert to message failed");
+        let pubkey = SECP256K1
+            .recover(&message, &signature)
+            .map_err(|err| format!("Recover public key from signature failed: {}", err))?;
+        let derived_lock_arg = pubkey_lock_arg(&pubkey, pubkey_format);
+        if &derived_lock_arg != lock_arg {
+            return Err(format!(
+                "Derived lock arg {:x} ({:?} public key) not match input lock arg {:x}",
+                derived_lock_arg, pubkey_format, lock_arg
+            ));
+        }
         let (recov_id, data) = signature.serialize_compact();
         let mut signature_bytes = [0u8; 65];
         signature_bytes[0..64].copy_from_slice(&data[0..64]);
@@ -101,6 +223,69 @@ pub fn check_alerts(rpc_client: &mut HttpRpcClient) {
     }
 }
 
+/// Tip block older than this (in milliseconds) means the node is probably still syncing
+const MAX_TIP_BLOCK_AGE: u64 = 60 * 60 * 1000;
+/// Blocks of the dev chain are only produced on demand, an old tip does not mean it is behind
+const DEV_CHAIN: &str = "ckb_dev";
+
+/// Tip and initial block download state of the node
+pub struct NodeSyncState {
+    pub tip_number: u64,
+    pub tip_timestamp: u64,
+    /// Milliseconds since the tip block, always 0 on the dev chain
+    pub tip_age: u64,
+    pub is_initial_block_download: bool,
+}
+
+impl NodeSyncState {
+    pub fn is_behind(&self) -> bool {
+        self.is_initial_block_download || self.tip_age > MAX_TIP_BLOCK_AGE
+    }
+}
+
+/// Check if the node is far behind the network tip, return the warning message if it is.
+/// A node failing to answer is reported as a warning too, the check itself never fails.
+pub fn check_node_sync_state(rpc_client: &mut HttpRpcClient) -> Option<String> {
+    match get_node_sync_state(rpc_client) {
+        Ok(ref state) if state.is_behind() => Some(format!(
+            "The node may be far behind the network tip (tip block: {}, {} minutes ago, initial block download: {}), resolved cells may be stale",
+            state.tip_number,
+            state.tip_age / 1000 / 60,
+            state.is_initial_block_download,
+        )),
+        Ok(_) => None,
+        Err(err) => Some(format!("Can not check if the node is synced: {}", err)),
+    }
+}
+
+pub fn get_node_sync_state(rpc_client: &mut HttpRpcClient) -> Result<NodeSyncState, String> {
+    let chain_info = rpc_client
+        .get_blockchain_info()
+        .call()
+        .map_err(|err| format!("Send get_blockchain_info error: {}", err))?;
+    let tip_header: HeaderView = rpc_client
+        .get_tip_header()
+        .call()
+        .map_err(|err| format!("Send get_tip_header error: {}", err))?;
+    let tip_timestamp = tip_header.inner.timestamp.value();
+    let tip_age = if chain_info.chain == DEV_CHAIN {
+        0
+    } else {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+            * 1000;
+        now.saturating_sub(tip_timestamp)
+    };
+    Ok(NodeSyncState {
+        tip_number: tip_header.inner.number.value(),
+        tip_timestamp,
+        tip_age,
+        is_initial_block_download: chain_info.is_initial_block_download,
+    })
+}
+
 pub fn get_genesis_info(
     genesis_info: &mut Option<GenesisInfo>,
     rpc_client: &mut HttpRpcClient,